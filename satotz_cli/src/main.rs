@@ -1,34 +1,391 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use satotz_lib::cnf::CNF;
-use satotz_lib::solver::Solver;
+use satotz_lib::local_search::LocalSearch;
+use satotz_lib::reconstruct::{parse_model, ParseError, ReconstructionStack};
+use satotz_lib::solver::{Decisions, LearningScheme, Mode, ModePolicy, Solver};
 use std::path::PathBuf;
 
 #[derive(Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// A dimacs cnf file
     #[clap(required = true)]
-    file: PathBuf,
+    file: Option<PathBuf>,
 
     /// Disable DLIS decision heuristic
-    #[arg(long)]
+    #[arg(long, conflicts_with = "decisions")]
     no_dlis: bool,
+
+    /// Use this decision heuristic in both search modes instead of DLIS, or VMTF and VSIDS
+    #[arg(long, value_enum)]
+    decisions: Option<DecisionHeuristic>,
+
+    /// Stay in one search mode instead of alternating between both
+    #[arg(long, value_enum)]
+    mode: Option<SearchMode>,
+
+    /// How clauses are learned from conflicts
+    #[arg(long, value_enum, default_value_t = Learning::FirstUip)]
+    learning: Learning,
+
+    /// Disable probing and other simplifications before the search
+    #[arg(long)]
+    no_simplify: bool,
+
+    /// Print statistics after solving
+    #[arg(long)]
+    stats: bool,
+
+    /// Print progress of the search as comments
+    #[arg(long)]
+    verbose: bool,
+
+    /// The algorithm used to solve the formula
+    #[arg(long, value_enum, default_value_t = Engine::Cdcl)]
+    engine: Engine,
+
+    /// Maximum number of flips of the local search engine
+    #[arg(long, default_value_t = 100_000_000)]
+    flip_limit: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Engine {
+    /// Conflict driven clause learning, complete
+    Cdcl,
+    /// ProbSAT local search, can only find models
+    LocalSearch,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum SearchMode {
+    /// Frequent restarts
+    Focused,
+    /// Rare restarts and target phases
+    Stable,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum DecisionHeuristic {
+    /// Variable move-to-front queue
+    Vmtf,
+    /// Variable activity scores
+    Vsids,
+    /// VMTF in focused mode and VSIDS in stable mode
+    VmtfVsids,
+    /// Conflict history-based branching, rewards for recent conflicts while assigned
+    Chb,
+    /// Learning rate branching, rewards for the share of conflicts while assigned
+    Lrb,
+    /// Literal with the most unresolved clauses
+    Dlis,
+    /// Literal with the most unresolved clauses weighted by 2^-size
+    JeroslowWang,
+    /// Variable with the most unresolved clauses of both literals weighted by 2^-size
+    TwoSidedJeroslowWang,
+    /// Variable with the most occurrences in the shortest clauses
+    Moms,
+    /// Variable with the most occurrences by clause size, compared lexicographically
+    Bohm,
+    /// Unassigned variable with the lowest index
+    FirstUnassigned,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum Learning {
+    /// Resolve until one literal of the conflict level is left
+    FirstUip,
+    /// Resolve until only the decision of the conflict level is left, as rel_sat does
+    LastUip,
+    /// Resolve until only decisions are left
+    AllDecisions,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Simplify a formula without solving it
+    Preprocess {
+        /// A dimacs cnf file
+        file: PathBuf,
+
+        /// Write the simplified dimacs cnf to the given file
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Write the reconstruction stack to the given file
+        #[arg(long)]
+        stack: PathBuf,
+    },
+    /// Extend a model of a preprocessed formula to a model of the original formula
+    Postsolve {
+        /// The reconstruction stack written by preprocess
+        #[arg(long)]
+        stack: PathBuf,
+
+        /// A model of the preprocessed formula, as `v` lines of a solver output or a MiniSat result
+        model: PathBuf,
+    },
 }
 
 fn main() {
     let args = Args::parse();
-    let cnf = CNF::from_file(args.file);
+
+    match args.command {
+        Some(Command::Preprocess {
+            file,
+            output,
+            stack,
+        }) => preprocess(file, output, stack),
+        Some(Command::Postsolve { stack, model }) => postsolve(stack, model),
+        None if args.engine == Engine::LocalSearch => local_search(args),
+        None => solve(args),
+    }
+}
+
+fn solve(args: Args) {
+    let cnf = CNF::from_file(args.file.expect("missing file"));
     let mut solver = Solver::from_cnf(cnf);
 
-    if args.no_dlis {
-        solver = solver.without_dlis();
+    let heuristic = if args.no_dlis {
+        Some(DecisionHeuristic::FirstUnassigned)
+    } else {
+        args.decisions
+    };
+    if let Some(heuristic) = heuristic {
+        let (focused, stable) = mode_policies(heuristic);
+        solver = solver
+            .with_focused_policy(focused)
+            .with_stable_policy(stable);
+    }
+
+    match args.mode {
+        Some(SearchMode::Focused) => solver = solver.with_single_mode(Mode::Focused),
+        Some(SearchMode::Stable) => solver = solver.with_single_mode(Mode::Stable),
+        None => {}
+    }
+
+    solver = solver.with_learning_scheme(match args.learning {
+        Learning::FirstUip => LearningScheme::FirstUip,
+        Learning::LastUip => LearningScheme::LastUip,
+        Learning::AllDecisions => LearningScheme::AllDecisions,
+    });
+
+    if !args.no_simplify {
+        solver.simplify();
     }
 
-    if solver.solve() {
+    let sat = if args.verbose {
+        solve_verbose(&mut solver)
+    } else {
+        solver.solve()
+    };
+
+    let exit_code = if sat {
         println!("s SATISFIABLE");
         println!("v {:?}", solver.assignment());
-        std::process::exit(10);
+        10
     } else {
         println!("s UNSATISFIABLE");
-        std::process::exit(20);
+        20
+    };
+
+    if args.stats {
+        println!("{}", solver.statistics());
+    }
+
+    std::process::exit(exit_code);
+}
+
+/// Returns the policies of the focused and stable mode that decide with the heuristic
+fn mode_policies(heuristic: DecisionHeuristic) -> (ModePolicy, ModePolicy) {
+    let decisions = match heuristic {
+        DecisionHeuristic::Vmtf => Some(Decisions::Vmtf),
+        DecisionHeuristic::Vsids => Some(Decisions::Vsids),
+        // the heuristics of the mode policies
+        DecisionHeuristic::VmtfVsids => None,
+        DecisionHeuristic::Chb => Some(Decisions::Chb),
+        DecisionHeuristic::Lrb => Some(Decisions::Lrb),
+        DecisionHeuristic::Dlis => Some(Decisions::Dlis),
+        DecisionHeuristic::JeroslowWang => Some(Decisions::JeroslowWang),
+        DecisionHeuristic::TwoSidedJeroslowWang => Some(Decisions::TwoSidedJeroslowWang),
+        DecisionHeuristic::Moms => Some(Decisions::Moms),
+        DecisionHeuristic::Bohm => Some(Decisions::Bohm),
+        DecisionHeuristic::FirstUnassigned => Some(Decisions::FirstUnassigned),
+    };
+    let with_decisions = |policy: ModePolicy| ModePolicy {
+        decisions: decisions.unwrap_or(policy.decisions),
+        ..policy
+    };
+    (
+        with_decisions(ModePolicy::focused()),
+        with_decisions(ModePolicy::stable()),
+    )
+}
+
+/// Solves step by step and prints the progress recorded in the statistics as comments
+fn solve_verbose(solver: &mut Solver) -> bool {
+    let mut last = solver.statistics().clone();
+    loop {
+        let (solver, result) = solver.step();
+        let stats = solver.statistics();
+        if stats.mode_switches != last.mode_switches {
+            println!(
+                "c mode switch {} after {} conflicts",
+                stats.mode_switches, stats.conflicts
+            );
+        }
+        if stats.inprocessing_rounds != last.inprocessing_rounds {
+            println!(
+                "c inprocessing round {} after {} conflicts: {} failed literals, {} vivified \
+                 clauses, {} ternary resolvents, {} subsumed clauses, {} eliminated variables",
+                stats.inprocessing_rounds,
+                stats.conflicts,
+                stats.failed_literals - last.failed_literals,
+                stats.vivified_clauses - last.vivified_clauses,
+                stats.ternary_resolvents - last.ternary_resolvents,
+                stats.subsumed_clauses - last.subsumed_clauses,
+                stats.eliminated_variables - last.eliminated_variables,
+            );
+        }
+        if stats.rephases != last.rephases {
+            println!(
+                "c rephase {} after {} conflicts",
+                stats.rephases, stats.conflicts
+            );
+        }
+        if stats.reductions != last.reductions {
+            println!(
+                "c reduction {} after {} conflicts: {} clauses deleted",
+                stats.reductions,
+                stats.conflicts,
+                stats.reduced_clauses - last.reduced_clauses
+            );
+        }
+        if (
+            stats.mode_switches,
+            stats.inprocessing_rounds,
+            stats.rephases,
+            stats.reductions,
+        ) != (
+            last.mode_switches,
+            last.inprocessing_rounds,
+            last.rephases,
+            last.reductions,
+        ) {
+            last = stats.clone();
+        }
+
+        if let Some(result) = result {
+            return result;
+        }
+    }
+}
+
+fn local_search(args: Args) {
+    let cnf = CNF::from_file(args.file.expect("missing file"));
+
+    let exit_code = if let Some(model) = LocalSearch::from_cnf(&cnf).solve(args.flip_limit) {
+        println!("s SATISFIABLE");
+        println!("v {:?}", model);
+        10
+    } else {
+        println!("s UNKNOWN");
+        0
+    };
+
+    std::process::exit(exit_code);
+}
+
+fn preprocess(file: PathBuf, output: PathBuf, stack: PathBuf) {
+    let mut solver = Solver::from_cnf(CNF::from_file(file));
+    solver.simplify();
+
+    std::fs::write(output, solver.simplified_cnf().to_dimacs()).expect("fs error");
+    std::fs::write(stack, solver.reconstruction_stack().to_string()).expect("fs error");
+}
+
+fn postsolve(stack: PathBuf, model: PathBuf) {
+    let text = std::fs::read_to_string(&stack).expect("fs error");
+    let stack = ReconstructionStack::from_text(&text).unwrap_or_else(|error| {
+        eprintln!("invalid stack file {}: {error}", stack.display());
+        std::process::exit(1)
+    });
+    let output = std::fs::read_to_string(&model).expect("fs error");
+
+    match postsolved(&stack, &output) {
+        Ok(result) => print!("{result}"),
+        Err(error) => {
+            eprintln!("invalid model file {}: {error}", model.display());
+            std::process::exit(1)
+        }
+    }
+}
+
+/// Returns the result for the original formula, a status other than satisfiable in the solver
+/// output of the simplified formula is passed through as competition style status
+fn postsolved(stack: &ReconstructionStack, output: &str) -> Result<String, ParseError> {
+    let status = output.lines().map(str::trim).find_map(|line| match line {
+        "SAT" => Some("s SATISFIABLE"),
+        "UNSAT" => Some("s UNSATISFIABLE"),
+        "INDET" => Some("s UNKNOWN"),
+        _ if line.starts_with("s ") => Some(line),
+        _ => None,
+    });
+    match status {
+        Some(status) if status != "s SATISFIABLE" => Ok(format!("{status}\n")),
+        _ => Ok(format!(
+            "s SATISFIABLE\nv {:?}\n",
+            stack.postsolve(&parse_model(output)?)
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mode_policies() {
+        assert_eq!(
+            mode_policies(DecisionHeuristic::VmtfVsids),
+            (ModePolicy::focused(), ModePolicy::stable())
+        );
+        let (focused, stable) = mode_policies(DecisionHeuristic::Lrb);
+        assert_eq!(
+            (focused.decisions, stable.decisions),
+            (Decisions::Lrb, Decisions::Lrb)
+        );
+        assert_eq!(stable.restarts, ModePolicy::stable().restarts);
+    }
+
+    #[test]
+    fn test_postsolve_status() {
+        let stack = ReconstructionStack::from_text("p stack 2\n2 -1 2 0\n").unwrap();
+        let postsolved = |output| postsolved(&stack, output).unwrap();
+        assert_eq!(
+            postsolved("s SATISFIABLE\nv 1 0\n"),
+            "s SATISFIABLE\nv [1, 2]\n"
+        );
+        assert_eq!(postsolved("1 0\n"), "s SATISFIABLE\nv [1, 2]\n");
+        assert_eq!(postsolved("SAT\n1 -2 0\n"), "s SATISFIABLE\nv [1, 2]\n");
+        assert_eq!(postsolved("s UNSATISFIABLE\n"), "s UNSATISFIABLE\n");
+        assert_eq!(postsolved("UNSAT\n"), "s UNSATISFIABLE\n");
+        assert_eq!(postsolved("INDET\n"), "s UNKNOWN\n");
+        assert_eq!(postsolved("c done\ns UNKNOWN\n"), "s UNKNOWN\n");
+    }
+
+    #[test]
+    fn test_postsolve_invalid_model() {
+        let stack = ReconstructionStack::from_text("p stack 2\n").unwrap();
+        assert_eq!(
+            postsolved(&stack, "s SATISFIABLE\nv 1 x 0\n"),
+            Err(ParseError {
+                line: 2,
+                message: "invalid literal x".to_string()
+            })
+        );
     }
 }
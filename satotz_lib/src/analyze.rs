@@ -1,21 +1,144 @@
 use crate::bcp::conflict::Conflict;
 use crate::bcp::trail::{Reason, Step, Trail};
 use crate::bcp::{trail, AddedClause, BcpContext};
-use crate::literal::Literal;
+use crate::clause::ClauseIndex;
+use crate::literal::{Literal, Variable};
+use crate::resize::Resize;
+
+/// The clause activity increment grows by the inverse of this factor after every conflict
+const CLAUSE_DECAY: f32 = 0.999;
+
+/// Clause activities are rescaled once the increment exceeds this value
+const MAX_CLAUSE_INCREMENT: f32 = 1e20;
+
+/// CHB rewards the variables assigned on the conflict level fully, and the variables assigned by
+/// earlier propagations without conflict by this factor
+const CHB_PROPAGATION_MULTIPLIER: f64 = 0.9;
+
+/// How the clause learned from a conflict is derived by resolving the conflicting clause with
+/// the reasons of its literals (Zhang et al. 2001)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LearningScheme {
+    /// resolves on the literals of the conflict level until a single one is left, the first unique
+    /// implication point
+    #[default]
+    FirstUip,
+    /// resolves on the literals of the conflict level until only its decision is left, which is
+    /// also the clause learned by rel_sat (Bayardo and Schrag 1997)
+    LastUip,
+    /// resolves on all implied literals until only decisions are left
+    AllDecisions,
+}
+
+/// Which variables are rewarded at conflicts for the learning-rate-based decision heuristics
+/// (Liang et al. 2016)
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Rewards {
+    #[default]
+    None,
+    /// conflict history-based branching: the variables assigned since the last conflict, by the
+    /// inverse of the number of conflicts since they last took part in one
+    Chb,
+    /// learning rate branching: the variables unassigned by the backtrack, by the share of the
+    /// conflicts they took part in while they were assigned
+    Lrb,
+}
 
 /// Temporary data during conflict analysis
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct ConflictAnalysis {
+    pub scheme: LearningScheme,
+
     /// maps a step index to true if the literal is in the current clause
     conflict_literals: Vec<bool>,
 
-    /// The derived clause, 1-UIP
+    /// The derived clause, its first literal is the only one on the conflict level
     derived_clause: Vec<Literal>,
 
     current_level_lit_count: usize,
 
     /// the decision level that will be the target of the non-chronological backtrack
     target_decision_level: u32,
+
+    /// number of distinct decision levels in the derived clause
+    glue: u32,
+
+    /// reasons strengthened while deriving the clause, with the literals they had before
+    strengthened_reasons: Vec<(ClauseIndex, Vec<Literal>)>,
+
+    /// variables of all literals visited while deriving the clause, bumped by the decision
+    /// heuristic
+    bumped_variables: Vec<Variable>,
+
+    /// added to the activity of learned clauses that take part in the analysis
+    clause_increment: f32,
+
+    pub rewards: Rewards,
+
+    /// maps a variable to the last conflict it took part in
+    last_conflict: Vec<u64>,
+
+    /// maps a variable to the number of conflicts it took part in since it was assigned, only
+    /// valid if its last conflict was after the assignment
+    participations: Vec<u32>,
+
+    /// variables rewarded by the last conflict or restart with their rewards
+    rewarded_variables: Vec<(Variable, f64)>,
+}
+
+impl Default for ConflictAnalysis {
+    fn default() -> Self {
+        ConflictAnalysis {
+            scheme: Default::default(),
+            conflict_literals: vec![],
+            derived_clause: vec![],
+            current_level_lit_count: 0,
+            target_decision_level: 0,
+            glue: 0,
+            strengthened_reasons: vec![],
+            bumped_variables: vec![],
+            clause_increment: 1.0,
+            rewards: Default::default(),
+            last_conflict: vec![],
+            participations: vec![],
+            rewarded_variables: vec![],
+        }
+    }
+}
+
+impl Resize for ConflictAnalysis {
+    fn resize(&mut self, var_count: usize) {
+        self.last_conflict.resize(var_count, 0);
+        self.participations.resize(var_count, 0);
+    }
+}
+
+impl ConflictAnalysis {
+    /// Returns the glue of the last derived clause
+    pub fn glue(&self) -> u32 {
+        self.glue
+    }
+
+    /// Returns the clause learned from the last conflict
+    pub fn derived_clause(&self) -> &[Literal] {
+        &self.derived_clause
+    }
+
+    /// Returns the indices of the reasons strengthened during the last analysis, with their
+    /// literals before, starting with the implied literal that was removed
+    pub fn strengthened_reasons(&self) -> &[(ClauseIndex, Vec<Literal>)] {
+        &self.strengthened_reasons
+    }
+
+    /// Returns the variables rewarded by the last conflict or restart with their rewards
+    pub fn rewarded_variables(&self) -> &[(Variable, f64)] {
+        &self.rewarded_variables
+    }
+
+    /// Returns the variables involved in the last conflict
+    pub fn bumped_variables_mut(&mut self) -> &mut [Variable] {
+        &mut self.bumped_variables
+    }
 }
 
 /// analyzes a  conflict
@@ -32,18 +155,127 @@ pub fn analyze(conflict: Conflict, analysis: &mut ConflictAnalysis, bcp: &mut Bc
         .max(bcp.trail.steps().len());
     analysis.conflict_literals.resize(new_len, false);
 
-    // derive the first UIP
-    derive_1_uip(conflict, analysis, bcp);
+    analysis.target_decision_level = match analysis.scheme {
+        LearningScheme::FirstUip => derive_1_uip(conflict, analysis, bcp),
+        LearningScheme::LastUip => derive_last_uip(conflict, analysis, bcp),
+        LearningScheme::AllDecisions => derive_all_decisions(conflict, analysis, bcp),
+    };
+    decay_clause_activities(analysis, bcp);
 
-    analysis.target_decision_level = prepare_for_backtracking(analysis, bcp);
+    analysis.glue = glue(&analysis.derived_clause, &bcp.trail);
 
+    bcp.trail.count_conflict();
+    reward_conflict(analysis, &bcp.trail);
     trail::backtrack(bcp, analysis.target_decision_level);
     learn_and_assign(analysis, bcp);
 }
 
-/// derives the first unique implication point clause from given implication graph and conflict
-pub fn derive_1_uip(conflict: Conflict, analysis: &mut ConflictAnalysis, bcp: &mut BcpContext) {
+/// Collects the rewards of the conflict for the decision heuristic, the variables involved in it
+/// are the bumped variables
+fn reward_conflict(analysis: &mut ConflictAnalysis, trail: &Trail) {
+    analysis.rewarded_variables.clear();
+    let conflict = trail.conflicts();
+
+    match analysis.rewards {
+        Rewards::None => {}
+        Rewards::Chb => {
+            for variable in &analysis.bumped_variables {
+                analysis.last_conflict[variable.as_index()] = conflict;
+            }
+
+            // the steps since the last conflict are the end of the trail
+            for step in trail.steps().iter().rev() {
+                let variable = step.assigned_literal.variable();
+                if trail.assigned_at(variable) + 1 != conflict {
+                    break;
+                }
+                let multiplier = if step.decision_level == trail.current_decision_level() {
+                    1.0
+                } else {
+                    CHB_PROPAGATION_MULTIPLIER
+                };
+                let age = conflict - analysis.last_conflict[variable.as_index()];
+                let reward = multiplier / (age + 1) as f64;
+                analysis.rewarded_variables.push((variable, reward));
+            }
+        }
+        Rewards::Lrb => {
+            for variable in &analysis.bumped_variables {
+                let index = variable.as_index();
+                if analysis.last_conflict[index] <= trail.assigned_at(*variable) {
+                    analysis.participations[index] = 0;
+                }
+                analysis.participations[index] += 1;
+                analysis.last_conflict[index] = conflict;
+            }
+            reward_unassigned(analysis, trail, analysis.target_decision_level);
+        }
+    }
+}
+
+/// Collects the LRB rewards of the variables that backtracking to the decision level unassigns,
+/// the number of conflicts they took part in divided by the number of conflicts while they were
+/// assigned
+pub fn reward_unassigned(analysis: &mut ConflictAnalysis, trail: &Trail, decision_level: u32) {
+    analysis.rewarded_variables.clear();
+    if analysis.rewards != Rewards::Lrb || decision_level >= trail.current_decision_level() {
+        return;
+    }
+
+    let conflict = trail.conflicts();
+    let first_step = trail.step_index(trail.decision(decision_level + 1).variable());
+    for step in &trail.steps()[first_step..] {
+        let variable = step.assigned_literal.variable();
+        let assigned_at = trail.assigned_at(variable);
+        if assigned_at == conflict {
+            continue;
+        }
+
+        let participations = if analysis.last_conflict[variable.as_index()] > assigned_at {
+            analysis.participations[variable.as_index()]
+        } else {
+            0
+        };
+        let reward = participations as f64 / (conflict - assigned_at) as f64;
+        analysis.rewarded_variables.push((variable, reward));
+    }
+}
+
+/// derives the first unique implication point clause from given implication graph and conflict,
+/// returns the backtrack level
+pub fn derive_1_uip(
+    conflict: Conflict,
+    analysis: &mut ConflictAnalysis,
+    bcp: &mut BcpContext,
+) -> u32 {
+    derive_uip(conflict, analysis, bcp, false)
+}
+
+/// derives the clause with the decision of the conflict level as the last unique implication
+/// point, returns the backtrack level
+pub fn derive_last_uip(
+    conflict: Conflict,
+    analysis: &mut ConflictAnalysis,
+    bcp: &mut BcpContext,
+) -> u32 {
+    derive_uip(conflict, analysis, bcp, true)
+}
+
+/// Resolves on the literals of the conflict level, starting with the last assigned one, until a
+/// single one is left, or only the decision if `last` is set
+fn derive_uip(
+    conflict: Conflict,
+    analysis: &mut ConflictAnalysis,
+    bcp: &mut BcpContext,
+    last: bool,
+) -> u32 {
     analysis.derived_clause.clear();
+    analysis.bumped_variables.clear();
+    analysis.strengthened_reasons.clear();
+
+    if let Conflict::LongClause(clause_index) = conflict {
+        bump_clause(analysis, bcp, clause_index);
+    }
 
     for &literal in conflict.get_literals(bcp) {
         add_literal(analysis, &bcp.trail, literal)
@@ -59,8 +291,9 @@ pub fn derive_1_uip(conflict: Conflict, analysis: &mut ConflictAnalysis, bcp: &m
 
         analysis.current_level_lit_count -= 1;
 
-        if analysis.current_level_lit_count == 0 {
-            // last literal at current decision level -> found a 1-UIP
+        if analysis.current_level_lit_count == 0 && (!last || step.reason == Reason::SolverDecision)
+        {
+            // last literal at current decision level -> found the UIP
             for &literal in &analysis.derived_clause {
                 let step_index = bcp.trail.step_index(literal.variable());
                 analysis.conflict_literals[step_index] = false;
@@ -68,14 +301,132 @@ pub fn derive_1_uip(conflict: Conflict, analysis: &mut ConflictAnalysis, bcp: &m
             analysis.derived_clause.push(!step.assigned_literal);
             break;
         } else {
+            if let Reason::Long(clause_index) = step.reason {
+                bump_clause(analysis, bcp, clause_index);
+            }
+
             // add asserting literals (that caused propagation) to get resolvent
+            let step = &bcp.trail.steps()[step_index];
             for &asserting_literal in step.reason.causing_literals(bcp) {
                 add_literal(analysis, &bcp.trail, asserting_literal);
             }
+
+            // on-the-fly subsumption (Han and Somenzi 2009): the resolvent is the reason without
+            // its implied literal if it is only one literal shorter. The literals of the reason
+            // assigned at the top level were not added to the resolvent, so they are not counted.
+            if let Reason::Long(clause_index) = bcp.trail.steps()[step_index].reason {
+                let resolvent_len =
+                    analysis.derived_clause.len() + analysis.current_level_lit_count;
+                let reason = bcp.long_clauses.literals(clause_index);
+                let top_level_len = reason
+                    .iter()
+                    .filter(|literal| {
+                        bcp.trail
+                            .get_step_for_variable(literal.variable())
+                            .decision_level
+                            == trail::TOP_DECISION_LEVEL
+                    })
+                    .count();
+                if analysis.current_level_lit_count >= 2
+                    && resolvent_len + top_level_len + 1 == reason.len()
+                {
+                    let reason = strengthen_reason(bcp, clause_index);
+                    analysis.strengthened_reasons.push((clause_index, reason));
+                }
+            }
         }
     }
 
     assert_eq!(analysis.current_level_lit_count, 0);
+    prepare_for_backtracking(analysis, bcp)
+}
+
+/// Removes the implied first literal from the reason of an assignment on the conflict level. The
+/// remaining literals of the conflict level come first and are watched, as they are unassigned
+/// after backtracking. The clause stops being a reason by that backtracking as well. Returns the
+/// literals of the reason before.
+fn strengthen_reason(bcp: &mut BcpContext, index: ClauseIndex) -> Vec<Literal> {
+    let literals = bcp.long_clauses.literals(index).to_vec();
+    let level = bcp.trail.current_decision_level();
+    let mut strengthened = literals[1..].to_vec();
+    strengthened
+        .sort_by_key(|l| bcp.trail.get_step_for_variable(l.variable()).decision_level != level);
+
+    bcp.proof.add_clause(&strengthened);
+    bcp.proof.delete_clause(&literals);
+    bcp.watch.unwatch_clause(index, &literals);
+    if let [a, b] = *strengthened {
+        bcp.long_clauses.delete_clause(index);
+        bcp.watch.add_binary_clause([a, b]);
+    } else {
+        bcp.long_clauses.strengthen_clause(index, &strengthened);
+        bcp.watch.watch_clause(index, &strengthened);
+    }
+    literals
+}
+
+/// derives the clause of the negated decisions that the conflict depends on, returns the
+/// backtrack level
+pub fn derive_all_decisions(
+    conflict: Conflict,
+    analysis: &mut ConflictAnalysis,
+    bcp: &mut BcpContext,
+) -> u32 {
+    analysis.derived_clause.clear();
+    analysis.bumped_variables.clear();
+    analysis.strengthened_reasons.clear();
+
+    if let Conflict::LongClause(clause_index) = conflict {
+        bump_clause(analysis, bcp, clause_index);
+    }
+
+    for &literal in conflict.get_literals(bcp) {
+        mark_literal(analysis, &bcp.trail, literal);
+    }
+
+    let mut asserting_literal = None;
+    for step_index in (0..bcp.trail.steps().len()).rev() {
+        if !std::mem::replace(&mut analysis.conflict_literals[step_index], false) {
+            continue;
+        }
+
+        let step = &bcp.trail.steps()[step_index];
+        if step.reason == Reason::SolverDecision {
+            if step.decision_level == bcp.trail.current_decision_level() {
+                asserting_literal = Some(!step.assigned_literal);
+            } else {
+                analysis.derived_clause.push(!step.assigned_literal);
+            }
+            continue;
+        }
+
+        if let Reason::Long(clause_index) = step.reason {
+            bump_clause(analysis, bcp, clause_index);
+        }
+
+        let step = &bcp.trail.steps()[step_index];
+        for &causing_literal in step.reason.causing_literals(bcp) {
+            mark_literal(analysis, &bcp.trail, causing_literal);
+        }
+    }
+
+    // the conflict depends on the decision of the conflict level, the only literal on that level
+    analysis
+        .derived_clause
+        .push(asserting_literal.expect("conflict without decision on the conflict level"));
+    prepare_for_backtracking(analysis, bcp)
+}
+
+/// Marks a literal of a lower level than the top level for resolution
+fn mark_literal(analysis: &mut ConflictAnalysis, trail: &Trail, literal: Literal) {
+    let step_index = trail.step_index(literal.variable());
+    if trail.steps()[step_index].decision_level == trail::TOP_DECISION_LEVEL {
+        return;
+    }
+
+    if !std::mem::replace(&mut analysis.conflict_literals[step_index], true) {
+        analysis.bumped_variables.push(literal.variable());
+    }
 }
 
 fn add_literal(analysis: &mut ConflictAnalysis, trail: &Trail, literal: Literal) {
@@ -93,6 +444,8 @@ fn add_literal(analysis: &mut ConflictAnalysis, trail: &Trail, literal: Literal)
         return;
     }
 
+    analysis.bumped_variables.push(literal.variable());
+
     if lit_decision_level == trail.current_decision_level() {
         // If the literal is assigned at the current decision level, we may want
         // to resolve on it.
@@ -104,6 +457,48 @@ fn add_literal(analysis: &mut ConflictAnalysis, trail: &Trail, literal: Literal)
     }
 }
 
+/// Updates the glue of a learned clause that takes part in the analysis, its literals are all
+/// assigned, marks it as used and bumps its activity
+fn bump_clause(analysis: &mut ConflictAnalysis, bcp: &mut BcpContext, index: ClauseIndex) {
+    if !bcp.long_clauses.clauses()[index].header().learned {
+        return;
+    }
+
+    let glue = glue(bcp.long_clauses.literals(index), &bcp.trail);
+    let header = bcp.long_clauses.find_clause_mut(index).header_mut();
+    header.glue = header.glue.min(glue);
+    header.used = true;
+    header.activity += analysis.clause_increment;
+}
+
+/// Increases the clause activity increment, which decays the activity of all clauses that are
+/// not bumped, and rescales all activities before they overflow
+fn decay_clause_activities(analysis: &mut ConflictAnalysis, bcp: &mut BcpContext) {
+    analysis.clause_increment /= CLAUSE_DECAY;
+    if analysis.clause_increment > MAX_CLAUSE_INCREMENT {
+        for index in 0..bcp.long_clauses.clauses().len() {
+            bcp.long_clauses
+                .find_clause_mut(index)
+                .header_mut()
+                .activity /= MAX_CLAUSE_INCREMENT;
+        }
+        analysis.clause_increment /= MAX_CLAUSE_INCREMENT;
+    }
+}
+
+/// Returns the number of distinct decision levels of the assigned literals (LBD)
+pub fn glue(literals: &[Literal], trail: &Trail) -> u32 {
+    let mut levels = literals
+        .iter()
+        .map(|l| trail.get_step_for_variable(l.variable()).decision_level)
+        .collect::<Vec<_>>();
+    levels.sort_unstable();
+    levels.dedup();
+    levels.len() as u32
+}
+
+/// Moves the asserting literal, the last one of the derived clause, to the front and the literal
+/// of the highest remaining decision level second, returns that level
 fn prepare_for_backtracking(analysis: &mut ConflictAnalysis, bcp: &mut BcpContext) -> u32 {
     let clause_length = analysis.derived_clause.len();
     analysis.derived_clause.swap(0, clause_length - 1);
@@ -128,9 +523,16 @@ fn prepare_for_backtracking(analysis: &mut ConflictAnalysis, bcp: &mut BcpContex
 
 /// adds the asserting clause to the formula and assigns the newly asserted literal
 fn learn_and_assign(conflict: &mut ConflictAnalysis, bcp: &mut BcpContext) {
-    let reason = match bcp.add_clause(&conflict.derived_clause) {
+    let reason = match bcp.learn_clause(&conflict.derived_clause) {
         AddedClause::Binary([_, b]) => Some(Reason::Binary(b)),
-        AddedClause::Long(clause_index) => Some(Reason::Long(clause_index)),
+        AddedClause::Long(clause_index) => {
+            let header = bcp.long_clauses.find_clause_mut(clause_index).header_mut();
+            header.learned = true;
+            header.glue = conflict.glue;
+            header.used = true;
+            header.activity = conflict.clause_increment;
+            Some(Reason::Long(clause_index))
+        }
         _ => None,
     };
 
@@ -153,7 +555,6 @@ mod test {
     use crate::bcp::{propagate, BcpContext};
     use crate::cnf::CNF;
     use crate::literal::Variable;
-    use crate::resize::Resize;
 
     #[test]
     fn test_non_chronological_backtracking() {
@@ -202,6 +603,153 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_learning_schemes() {
+        let cnf = CNF::from_dimacs("-1 7 0\n-2 3 0\n-7 -3 4 0\n-4 5 0\n-4 6 0\n-5 -6 0\n");
+        let expected = [
+            (LearningScheme::FirstUip, vec![-4], 0),
+            (LearningScheme::LastUip, vec![-2, -7], 1),
+            (LearningScheme::AllDecisions, vec![-2, -1], 1),
+        ];
+
+        for (scheme, clause, level) in expected {
+            let mut analysis = ConflictAnalysis {
+                scheme,
+                ..Default::default()
+            };
+            let mut bcp = BcpContext::from_cnf(&cnf);
+
+            decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+            propagate(&mut bcp).unwrap();
+            decide_and_assign(&mut bcp, Literal::from_dimacs(2));
+            let conflict = propagate(&mut bcp).unwrap_err();
+            analyze(conflict, &mut analysis, &mut bcp);
+
+            // the asserting literal is the first one
+            assert_eq!(
+                analysis.derived_clause,
+                clause
+                    .into_iter()
+                    .map(Literal::from_dimacs)
+                    .collect::<Vec<_>>()
+            );
+            assert_eq!(analysis.target_decision_level, level);
+        }
+    }
+
+    #[test]
+    fn test_rewards() {
+        let cnf = CNF::from_dimacs("-1 7 0\n-2 3 0\n-7 -3 4 0\n-4 5 0\n-4 6 0\n-5 -6 0\n");
+        let expected = [
+            // 4, 5 and 6 are in the conflict, 1 and 7 were propagated before the conflict level
+            (
+                Rewards::Chb,
+                vec![
+                    (6, 1.0),
+                    (5, 1.0),
+                    (4, 1.0),
+                    (3, 0.5),
+                    (2, 0.5),
+                    (7, 0.45),
+                    (1, 0.45),
+                ],
+            ),
+            // the learned unit unassigns all variables after one conflict
+            (
+                Rewards::Lrb,
+                vec![
+                    (1, 0.0),
+                    (7, 0.0),
+                    (2, 0.0),
+                    (3, 0.0),
+                    (4, 1.0),
+                    (5, 1.0),
+                    (6, 1.0),
+                ],
+            ),
+        ];
+
+        for (rewards, rewarded) in expected {
+            let mut analysis = ConflictAnalysis {
+                rewards,
+                ..Default::default()
+            };
+            analysis.resize(7);
+            let mut bcp = BcpContext::from_cnf(&cnf);
+
+            decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+            propagate(&mut bcp).unwrap();
+            decide_and_assign(&mut bcp, Literal::from_dimacs(2));
+            let conflict = propagate(&mut bcp).unwrap_err();
+            analyze(conflict, &mut analysis, &mut bcp);
+
+            let rewarded = rewarded
+                .into_iter()
+                .map(|(v, reward)| (Variable::from_dimacs(v), reward))
+                .collect::<Vec<_>>();
+            assert_eq!(analysis.rewarded_variables(), rewarded);
+        }
+    }
+
+    #[test]
+    fn test_strengthen_subsumed_reason() {
+        let cnf = CNF::from_dimacs("-1 2 0\n-1 3 0\n-1 8 0\n-2 -3 -8 4 0\n-4 6 0\n-6 -2 -3 -8 0\n");
+        let mut analysis = ConflictAnalysis::default();
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+        let conflict = propagate(&mut bcp).unwrap_err();
+        analyze(conflict, &mut analysis, &mut bcp);
+        assert_eq!(analysis.derived_clause, vec![Literal::from_dimacs(-1)]);
+
+        // -6 is implied before 4, resolving the conflict (-4 6) with the reason of 4 and then with
+        // the reason of -6 gives the reason of -6 without that literal
+        let sorted = |index| {
+            let mut literals = bcp.long_clauses.literals(index).to_vec();
+            literals.sort_unstable();
+            literals
+        };
+        assert_eq!(sorted(0), [-2, -3, 4, -8].map(Literal::from_dimacs));
+        assert_eq!(sorted(1), [-2, -3, -8].map(Literal::from_dimacs));
+
+        // the strengthened clause is watched
+        propagate(&mut bcp).unwrap();
+        decide_and_assign(&mut bcp, Literal::from_dimacs(2));
+        decide_and_assign(&mut bcp, Literal::from_dimacs(3));
+        propagate(&mut bcp).unwrap();
+        assert!(bcp.assignment.literal_is_true(Literal::from_dimacs(-8)));
+    }
+
+    #[test]
+    fn test_strengthen_reason_with_top_level_literal() {
+        // both clauses contain -9, which is false at the top level, and the resolvent of them
+        // contains -3 and -5, which are not both in either clause
+        let cnf = CNF::from_dimacs("9 0\n-1 2 0\n-1 3 0\n-1 5 0\n-9 -2 -3 4 0\n-9 -4 -2 -5 0\n");
+        let mut analysis = ConflictAnalysis::default();
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        propagate(&mut bcp).unwrap();
+
+        decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+        let conflict = propagate(&mut bcp).unwrap_err();
+        analyze(conflict, &mut analysis, &mut bcp);
+        assert!(analysis.strengthened_reasons().is_empty());
+        assert_eq!(bcp.long_clauses.literals(0).len(), 4);
+        assert_eq!(bcp.long_clauses.literals(1).len(), 4);
+
+        // without -5 the resolvent is the reason without the implied and the top level literal,
+        // either clause can be the reason
+        let cnf = CNF::from_dimacs("9 0\n-1 2 0\n-1 3 0\n-9 -2 -3 4 0\n-9 -4 -2 -3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        propagate(&mut bcp).unwrap();
+
+        decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+        let conflict = propagate(&mut bcp).unwrap_err();
+        analyze(conflict, &mut analysis, &mut bcp);
+        assert_eq!(analysis.strengthened_reasons().len(), 1);
+        let lengths = [0, 1].map(|index| bcp.long_clauses.literals(index).len());
+        assert_eq!(lengths.iter().min(), Some(&3));
+    }
+
     #[test]
     fn test_learn_unit_clause() {
         let cnf = CNF::from_dimacs("-1 2 0\n-1 3 0\n-2 -3 0\n-4 1 0\n");
@@ -282,6 +830,29 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_update_glue_of_used_clause() {
+        let cnf = CNF::from_dimacs("-1 2 0\n-1 3 0\n-2 -3 -4 -5 0\n-6 7 0\n-7 4 0\n-7 5 0\n");
+
+        let mut analysis = ConflictAnalysis::default();
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let header = bcp.long_clauses.find_clause_mut(0).header_mut();
+        header.learned = true;
+        header.glue = 10;
+
+        decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+        propagate(&mut bcp).unwrap();
+        decide_and_assign(&mut bcp, Literal::from_dimacs(6));
+        let conflict = propagate(&mut bcp).unwrap_err();
+        analyze(conflict, &mut analysis, &mut bcp);
+
+        // the conflicting clause has literals on the two decision levels
+        let header = bcp.long_clauses.clauses()[0].header();
+        assert_eq!(header.glue, 2);
+        assert!(header.used);
+        assert!(header.activity > 0.0);
+    }
+
     #[test]
     fn binary_clause() {
         let cnf = CNF::from_dimacs("-1 2 0\n-1 3 0\n-2 -4 -5 0\n-6 7 0\n-7 4 0\n-7 5 0\n");
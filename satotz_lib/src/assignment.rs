@@ -53,6 +53,10 @@ impl VariableAssignment {
         self.partial[var.index() as usize]
     }
 
+    pub fn is_unknown(&self, var: Variable) -> bool {
+        self.value(var) == AssignedValue::Unknown
+    }
+
     pub fn literal_value(&self, lit: Literal) -> AssignedValue {
         let variable_value = self.value(lit.variable());
         if lit.is_positive() {
@@ -70,6 +74,10 @@ impl VariableAssignment {
         self.literal_value(lit) == AssignedValue::Unknown
     }
 
+    pub fn variable_count(&self) -> usize {
+        self.partial.len()
+    }
+
     pub fn assignment(&self) -> Vec<Literal> {
         self.partial
             .iter()
@@ -1,24 +1,25 @@
 use crate::assignment::{AssignedValue, VariableAssignment};
-use crate::bcp::binary_clauses::BinaryClauses;
 use crate::bcp::conflict::Conflict;
 use crate::bcp::long_clauses::LongClauses;
+use crate::bcp::phases::Phases;
 use crate::bcp::trail::{Reason, Step, Trail};
-use crate::bcp::watch::Watchlists;
+use crate::bcp::watch::{Watch, Watchlists};
 use crate::clause::ClauseIndex;
 use crate::cnf::CNF;
 use crate::literal::Literal;
+use crate::proof::Proof;
 use crate::resize::Resize;
 
-pub mod binary_clauses;
 pub mod conflict;
 pub mod long_clauses;
+pub mod phases;
 pub mod trail;
-mod watch;
+pub mod watch;
 
 /// Reference to an added clause
 pub enum AddedClause {
     Empty,
-    Unit(Literal),
+    Unit,
     Binary([Literal; 2]),
     Long(ClauseIndex),
 }
@@ -28,18 +29,23 @@ pub enum AddedClause {
 pub struct BcpContext {
     pub is_unsat: bool,
     pub assignment: VariableAssignment,
-    pub binary_clauses: BinaryClauses,
     pub long_clauses: LongClauses,
     pub watch: Watchlists,
     pub trail: Trail,
+    pub proof: Proof,
+    /// a long clause that does not take part in propagation, e.g. while it is vivified
+    pub ignored_clause: Option<ClauseIndex>,
+    /// measure for the propagation effort, increased for every visited watch
+    pub ticks: u64,
+    pub phases: Phases,
 }
 
 impl Resize for BcpContext {
     fn resize(&mut self, var_count: usize) {
         self.assignment.resize(var_count);
-        self.binary_clauses.resize(var_count);
         self.watch.resize(var_count);
         self.trail.resize(var_count);
+        self.phases.resize(var_count);
     }
 }
 
@@ -56,15 +62,85 @@ impl BcpContext {
         bcp
     }
 
+    /// Returns the top level units, the binary clauses and the irredundant long clauses, which
+    /// together are equivalent to the formula
+    pub fn irredundant_clauses(&mut self) -> Vec<Vec<Literal>> {
+        let mut clauses = self
+            .trail
+            .steps()
+            .iter()
+            .filter(|s| s.decision_level == trail::TOP_DECISION_LEVEL)
+            .map(|s| vec![s.assigned_literal])
+            .collect::<Vec<_>>();
+
+        for code in 0..self.assignment.variable_count() * 2 {
+            let a = Literal::from_code(code);
+            for b in self.watch.binary_clauses(a) {
+                if a < b {
+                    clauses.push(vec![a, b]);
+                }
+            }
+        }
+
+        for clause in self.long_clauses.clauses() {
+            if !clause.header().deleted && !clause.header().learned {
+                clauses.push(clause.literals().to_vec());
+            }
+        }
+
+        clauses
+    }
+
+    /// Adds a clause that was derived by the solver and logs it to the proof
+    pub fn learn_clause(&mut self, literals: &[Literal]) -> AddedClause {
+        self.proof.add_clause(literals);
+        self.add_clause(literals)
+    }
+
+    /// Returns true if the sorted and deduplicated literals are a binary or irredundant long
+    /// clause of the formula
+    pub fn contains_clause(&self, literals: &[Literal]) -> bool {
+        if let [a, b] = *literals {
+            return self.watch.contains_binary_clause([a, b]);
+        }
+        self.long_clauses
+            .find_irredundant_clause(literals)
+            .is_some()
+    }
+
+    /// Marks the formula as unsat after a conflict on the top decision level
+    pub fn set_unsat(&mut self) {
+        if !self.is_unsat {
+            self.proof.add_clause(&[]);
+            self.is_unsat = true;
+        }
+    }
+
+    /// Removes deleted long clauses and rebuilds their watches, only possible on the top level
+    pub fn collect_garbage(&mut self) {
+        assert_eq!(
+            self.trail.current_decision_level(),
+            trail::TOP_DECISION_LEVEL
+        );
+
+        self.trail.forget_top_level_reasons();
+        self.long_clauses.collect_garbage();
+
+        self.watch.clear_clause_watches();
+        for (index, clause) in self.long_clauses.clauses().iter().enumerate() {
+            self.watch.watch_clause(index, clause.literals());
+        }
+    }
+
     pub fn add_clause(&mut self, literals: &[Literal]) -> AddedClause {
         match *literals {
             [] => {
-                self.is_unsat = true;
+                self.set_unsat();
                 AddedClause::Empty
             }
             [a] => {
                 if self.assignment.literal_value(a) == AssignedValue::False {
-                    self.is_unsat = true;
+                    self.set_unsat();
                     return AddedClause::Empty;
                 }
                 let step = Step {
@@ -73,15 +149,15 @@ impl BcpContext {
                     reason: Reason::Unit,
                 };
                 trail::assign(&mut self.assignment, &mut self.trail, step);
-                AddedClause::Unit(a)
+                AddedClause::Unit
             }
             [a, b] => {
-                self.binary_clauses.add_clause([a, b]);
+                self.watch.add_binary_clause([a, b]);
                 AddedClause::Binary([a, b])
             }
-            [a, b, ..] => {
+            [_, _, ..] => {
                 let index = self.long_clauses.add_clause(literals);
-                self.watch.watch_clause(index, [a, b]);
+                self.watch.watch_clause(index, literals);
                 AddedClause::Long(index)
             }
         }
@@ -91,9 +167,15 @@ impl BcpContext {
 /// Repeatedly execute BCP until a fixpoint or conflict is reached
 #[inline(always)]
 pub fn propagate(bcp: &mut BcpContext) -> Result<(), Conflict> {
+    let result = propagate_to_fixpoint(bcp);
+    bcp.trail.update_conflict_free(result.is_err());
+    result
+}
+
+#[inline(always)]
+fn propagate_to_fixpoint(bcp: &mut BcpContext) -> Result<(), Conflict> {
     while let Some(literal) = bcp.trail.next_unpropagated_literal() {
-        bcp_binary_clauses(bcp, literal)?;
-        bcp_long_clauses(bcp, literal)?;
+        bcp_watches(bcp, literal)?;
         bcp.trail.increase_propagated();
     }
 
@@ -101,127 +183,214 @@ pub fn propagate(bcp: &mut BcpContext) -> Result<(), Conflict> {
     Ok(())
 }
 
+/// Outcome of visiting a single watch during propagation
+enum Visited {
+    /// keep the watch
+    Keep,
+    /// drop the watch, its clause was deleted or is now watched by another literal
+    Drop,
+    /// keep the watch and stop propagating, its clause is falsified
+    Conflict(Conflict),
+}
+
 #[inline(always)]
-fn bcp_binary_clauses(bcp: &mut BcpContext, literal: Literal) -> Result<(), Conflict> {
+fn bcp_watches(bcp: &mut BcpContext, literal: Literal) -> Result<(), Conflict> {
     // look at all clauses containing !literal
     let not_literal = !literal;
 
-    for entry in bcp.binary_clauses.clauses(not_literal) {
-        match bcp.assignment.literal_value(entry.other_literal) {
-            // the other literal is true -> already satisfied
-            AssignedValue::True => {
-                continue;
+    let mut watches = bcp.watch.take_watchlist(not_literal);
+    bcp.ticks += 1 + watches.len() as u64;
+
+    let mut result = Ok(());
+    watches.retain_mut(|watch| {
+        // after a conflict the remaining watches are kept unvisited
+        if result.is_err() {
+            return true;
+        }
+
+        let visited = match watch {
+            Watch::Binary(other) => bcp_binary_clause(bcp, not_literal, *other),
+            Watch::Ternary(clause_index, other_literals) => {
+                bcp_ternary_clause(bcp, *clause_index as ClauseIndex, *other_literals)
             }
-            // the other literal is false -> conflict
-            AssignedValue::False => {
-                return Err(Conflict::BinaryClause([not_literal, entry.other_literal]));
+            Watch::Long(clause_index, blocker) => {
+                bcp_long_clause(bcp, not_literal, *clause_index as ClauseIndex, blocker)
             }
-            // the other literal is unassigned -> clause became unit, propagate the other literal
-            AssignedValue::Unknown => {
-                let step = Step {
-                    assigned_literal: entry.other_literal,
-                    decision_level: bcp.trail.current_decision_level(),
-                    reason: Reason::Binary(not_literal),
-                };
-                trail::assign(&mut bcp.assignment, &mut bcp.trail, step);
+        };
+
+        match visited {
+            Visited::Keep => true,
+            Visited::Drop => false,
+            Visited::Conflict(conflict) => {
+                result = Err(conflict);
+                true
             }
         }
+    });
+
+    bcp.watch.place_watchlist(not_literal, watches);
+
+    result
+}
+
+#[inline(always)]
+fn bcp_binary_clause(bcp: &mut BcpContext, not_literal: Literal, other: Literal) -> Visited {
+    match bcp.assignment.literal_value(other) {
+        // the other literal is true -> already satisfied
+        AssignedValue::True => {}
+        // the other literal is false -> conflict
+        AssignedValue::False => {
+            return Visited::Conflict(Conflict::BinaryClause([not_literal, other]));
+        }
+        // the other literal is unassigned -> clause became unit, propagate the other literal
+        AssignedValue::Unknown => {
+            let step = Step {
+                assigned_literal: other,
+                decision_level: bcp.trail.current_decision_level(),
+                reason: Reason::Binary(not_literal),
+            };
+            trail::assign(&mut bcp.assignment, &mut bcp.trail, step);
+        }
     }
 
-    Ok(())
+    Visited::Keep
 }
 
 #[inline(always)]
-fn bcp_long_clauses(bcp: &mut BcpContext, literal: Literal) -> Result<(), Conflict> {
-    let mut result = Ok(());
+fn bcp_ternary_clause(
+    bcp: &mut BcpContext,
+    clause_index: ClauseIndex,
+    other_literals: [Literal; 2],
+) -> Visited {
+    let [b, c] = other_literals;
+    let value_b = bcp.assignment.literal_value(b);
+    if value_b == AssignedValue::True {
+        return Visited::Keep;
+    }
+    let value_c = bcp.assignment.literal_value(c);
+    let implied = match (value_b, value_c) {
+        // already satisfied or not yet unit
+        (_, AssignedValue::True) | (AssignedValue::Unknown, AssignedValue::Unknown) => {
+            return Visited::Keep;
+        }
+        (AssignedValue::Unknown, _) => Some(b),
+        (_, AssignedValue::Unknown) => Some(c),
+        _ => None,
+    };
 
-    let watched_literal_1 = !literal;
+    if bcp.ignored_clause == Some(clause_index) {
+        return Visited::Keep;
+    }
 
-    let mut watches = bcp.watch.take_watchlist(watched_literal_1);
-    let mut removed_watch_indices: Vec<usize> = vec![];
+    let clause = bcp.long_clauses.find_clause_mut(clause_index);
 
-    'watches: for (watch_index, watch) in watches.iter_mut().enumerate() {
-        // the clause is already satisfied by our stored satisfying literal
-        if bcp.assignment.literal_is_true(watch.satisfying_literal) {
-            continue;
-        }
+    // the clause was removed from the formula, drop the watch
+    if clause.header().deleted {
+        return Visited::Drop;
+    }
 
-        let clause = bcp.long_clauses.find_clause_mut(watch.clause_index);
-        let literals = clause.literals_mut();
+    let Some(implied) = implied else {
+        return Visited::Conflict(Conflict::LongClause(clause_index));
+    };
 
-        // get the other watched literal
-        let watched_literal_2 = if watched_literal_1 == literals[0] {
-            literals[1]
-        } else {
-            literals[0]
-        };
+    // the implied literal of a reason is the first one
+    let literals = clause.literals_mut();
+    let position = literals.iter().position(|l| *l == implied).unwrap();
+    literals.swap(0, position);
 
-        // the clause is already satisfied by the other watched literal
-        if bcp.assignment.literal_is_true(watched_literal_2) {
-            watch.satisfying_literal = watched_literal_2;
-            continue;
-        }
+    let step = Step {
+        assigned_literal: implied,
+        decision_level: bcp.trail.current_decision_level(),
+        reason: Reason::Long(clause_index),
+    };
+    trail::assign(&mut bcp.assignment, &mut bcp.trail, step);
 
-        // search a non-false non-watched literal to replace watched_literal_1
-        for i in 2..literals.len() {
-            let current_literal = literals[i];
-            match bcp.assignment.literal_value(current_literal) {
-                AssignedValue::True => {
-                    watch.satisfying_literal = current_literal;
-                    continue 'watches;
-                }
-                AssignedValue::Unknown => {
-                    // change the watches
-                    removed_watch_indices.push(watch_index);
-                    watch.satisfying_literal = watched_literal_2;
-                    bcp.watch.add_watch(current_literal, *watch);
-                    // change the clauses literal order
-                    literals[0] = current_literal;
-                    literals[1] = watched_literal_2;
-                    literals[i] = watched_literal_1;
-                    continue 'watches;
-                }
-                _ => {}
-            }
-        }
+    Visited::Keep
+}
 
-        // did not find a non-false non-watched literal
-        match bcp.assignment.literal_value(watched_literal_2) {
-            // clause became unit, propagate `watched_literal_2`
-            AssignedValue::True | AssignedValue::Unknown => {
-                literals[0] = watched_literal_2;
-                literals[1] = watched_literal_1;
+#[inline(always)]
+fn bcp_long_clause(
+    bcp: &mut BcpContext,
+    watched_literal_1: Literal,
+    clause_index: ClauseIndex,
+    blocker: &mut Literal,
+) -> Visited {
+    // the clause is already satisfied by the blocking literal or is ignored
+    if bcp.assignment.literal_is_true(*blocker) || bcp.ignored_clause == Some(clause_index) {
+        return Visited::Keep;
+    }
 
-                let step = Step {
-                    assigned_literal: watched_literal_2,
-                    decision_level: bcp.trail.current_decision_level(),
-                    reason: Reason::Long(watch.clause_index),
-                };
+    let clause = bcp.long_clauses.find_clause_mut(clause_index);
+
+    // the clause was removed from the formula, drop the watch
+    if clause.header().deleted {
+        return Visited::Drop;
+    }
 
-                trail::assign(&mut bcp.assignment, &mut bcp.trail, step);
+    let literals = clause.literals_mut();
+
+    // get the other watched literal
+    let watched_literal_2 = if watched_literal_1 == literals[0] {
+        literals[1]
+    } else {
+        literals[0]
+    };
+
+    // the clause is already satisfied by the other watched literal
+    if bcp.assignment.literal_is_true(watched_literal_2) {
+        *blocker = watched_literal_2;
+        return Visited::Keep;
+    }
+
+    // search a non-false non-watched literal to replace watched_literal_1
+    for i in 2..literals.len() {
+        let current_literal = literals[i];
+        match bcp.assignment.literal_value(current_literal) {
+            AssignedValue::True => {
+                *blocker = current_literal;
+                return Visited::Keep;
             }
-            // all literals are false, conflict
-            AssignedValue::False => {
-                result = Err(Conflict::LongClause(watch.clause_index));
-                break;
+            AssignedValue::Unknown => {
+                // change the watches
+                let watch = Watch::Long(clause_index as u32, watched_literal_2);
+                bcp.watch.add_watch(current_literal, watch);
+                // change the clauses literal order
+                literals[0] = current_literal;
+                literals[1] = watched_literal_2;
+                literals[i] = watched_literal_1;
+                return Visited::Drop;
             }
+            _ => {}
         }
     }
 
-    // remove the invalidated watches
-    removed_watch_indices.into_iter().rev().for_each(|i| {
-        watches.remove(i);
-    });
-
-    bcp.watch.place_watchlist(!literal, watches);
-
-    result
+    // did not find a non-false non-watched literal
+    match bcp.assignment.literal_value(watched_literal_2) {
+        // clause became unit, propagate `watched_literal_2`
+        AssignedValue::True | AssignedValue::Unknown => {
+            literals[0] = watched_literal_2;
+            literals[1] = watched_literal_1;
+
+            let step = Step {
+                assigned_literal: watched_literal_2,
+                decision_level: bcp.trail.current_decision_level(),
+                reason: Reason::Long(clause_index),
+            };
+            trail::assign(&mut bcp.assignment, &mut bcp.trail, step);
+
+            *blocker = watched_literal_2;
+            Visited::Keep
+        }
+        // all literals are false, conflict
+        AssignedValue::False => Visited::Conflict(Conflict::LongClause(clause_index)),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cnf::CNF;
+    use crate::literal::Variable;
 
     #[test]
     fn test_basic_bcp() {
@@ -257,6 +426,74 @@ mod tests {
         };
     }
 
+    #[test]
+    fn test_conflict_keeps_watches() {
+        let cnf = CNF::from_dimacs("-1 2 0\n-1 -2 0\n-1 3 4 5 0\n-1 -3 6 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        for _ in 0..2 {
+            trail::decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+            assert!(matches!(
+                propagate(&mut bcp),
+                Err(Conflict::BinaryClause(_))
+            ));
+            trail::backtrack(&mut bcp, trail::TOP_DECISION_LEVEL);
+        }
+
+        // the watches after the conflicting binary clause are still in place
+        trail::decide_and_assign(&mut bcp, Literal::from_dimacs(-2));
+        assert!(propagate(&mut bcp).is_ok());
+        assert!(bcp.assignment.literal_is_true(Literal::from_dimacs(-1)));
+    }
+
+    #[test]
+    fn test_contains_clause() {
+        let cnf = CNF::from_dimacs("1 2 0\n1 2 3 0\n-1 2 3 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let clause = |dimacs: &[i32]| {
+            let mut literals = dimacs
+                .iter()
+                .map(|&l| Literal::from_dimacs(l))
+                .collect::<Vec<_>>();
+            literals.sort_unstable();
+            literals
+        };
+
+        assert!(bcp.contains_clause(&clause(&[2, 1])));
+        assert!(bcp.contains_clause(&clause(&[3, 2, 1])));
+        assert!(bcp.contains_clause(&clause(&[4, 3, 2, -1])));
+        assert!(!bcp.contains_clause(&clause(&[1, 3])));
+        assert!(!bcp.contains_clause(&clause(&[1, 2, 4])));
+
+        // deleted and learned clauses are not part of the formula
+        bcp.long_clauses.delete_clause(1);
+        assert!(!bcp.contains_clause(&clause(&[-1, 2, 3, 4])));
+        bcp.long_clauses.find_clause_mut(0).header_mut().learned = true;
+        assert!(!bcp.contains_clause(&clause(&[1, 2, 3])));
+    }
+
+    #[test]
+    fn test_ternary_clauses() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n-3 4 5 0\n-3 4 -5 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        trail::decide_and_assign(&mut bcp, Literal::from_dimacs(-1));
+        trail::decide_and_assign(&mut bcp, Literal::from_dimacs(-2));
+        trail::decide_and_assign(&mut bcp, Literal::from_dimacs(-4));
+
+        // 3 implies 5 by the second clause, which falsifies the third
+        match propagate(&mut bcp) {
+            Err(Conflict::LongClause(index)) => assert_eq!(index, 2),
+            _ => panic!("expected a conflict"),
+        };
+
+        // the implied literal is moved to the front of its reason
+        let step = bcp.trail.get_step_for_variable(Variable::from_dimacs(3));
+        assert_eq!(step.assigned_literal, Literal::from_dimacs(3));
+        assert_eq!(step.reason, Reason::Long(0));
+        assert_eq!(bcp.long_clauses.literals(0)[0], Literal::from_dimacs(3));
+    }
+
     #[test]
     fn test_exercise_2_bcp_fixpoint() {
         let cnf = CNF::from_dimacs("1 2 3 0\n-1 2 0\n-2 0\n-1 -2 0\n-3 -4 5 6 0\n-3 4 0\n");
@@ -10,7 +10,7 @@ pub enum Conflict {
 }
 
 impl Conflict {
-    pub fn get_literals<'a>(&'a self, context: &'a BcpContext) -> &[Literal] {
+    pub fn get_literals<'a>(&'a self, context: &'a BcpContext) -> &'a [Literal] {
         match self {
             Conflict::BinaryClause(literals) => literals,
             Conflict::LongClause(clause_index) => context.long_clauses.literals(*clause_index),
@@ -1,10 +1,26 @@
 use crate::clause::{Clause, ClauseIndex};
 use crate::literal::Literal;
+use std::collections::HashMap;
 
 /// Holds all long clauses during propagation
 #[derive(Default, Debug)]
 pub struct LongClauses {
     clauses: Vec<Clause>,
+    /// maps the hash of the literals of a clause to the clauses with that hash which are not
+    /// deleted, so that identical clauses are found without searching the watchlists
+    lookup: HashMap<u64, Vec<ClauseIndex>>,
+}
+
+/// Hash of a set of literals that does not depend on their order, because propagation reorders
+/// the literals of a clause
+fn literals_hash(literals: &[Literal]) -> u64 {
+    literals.iter().fold(0u64, |hash, literal| {
+        // finalizer of splitmix64
+        let mut z = (literal.as_code() as u64).wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        hash.wrapping_add(z ^ (z >> 31))
+    })
 }
 
 impl LongClauses {
@@ -15,7 +31,62 @@ impl LongClauses {
     pub fn add_clause(&mut self, literals: &[Literal]) -> usize {
         let clause = Clause::from_literals(literals);
         self.clauses.push(clause);
-        self.clauses.len() - 1
+        let index = self.clauses.len() - 1;
+        self.lookup
+            .entry(literals_hash(literals))
+            .or_default()
+            .push(index);
+        index
+    }
+
+    /// Marks the clause as deleted, it must not be the reason of an assignment above the top level
+    pub fn delete_clause(&mut self, index: ClauseIndex) {
+        if !self.clauses[index].header().deleted {
+            self.unlink(index);
+        }
+        self.clauses[index].header_mut().deleted = true;
+    }
+
+    /// Replaces the literals of a clause by a subset, the watches stay valid if it keeps the first
+    /// two literals in place
+    pub fn strengthen_clause(&mut self, index: ClauseIndex, literals: &[Literal]) {
+        let header = *self.clauses[index].header();
+        if !header.deleted {
+            self.unlink(index);
+            self.lookup
+                .entry(literals_hash(literals))
+                .or_default()
+                .push(index);
+        }
+        self.clauses[index] = Clause::from_literals(literals);
+        *self.clauses[index].header_mut() = header;
+    }
+
+    /// Removes all deleted clauses, invalidating all clause indices
+    pub fn collect_garbage(&mut self) {
+        self.clauses.retain(|c| !c.header().deleted);
+
+        self.lookup.clear();
+        for (index, clause) in self.clauses.iter().enumerate() {
+            self.lookup
+                .entry(literals_hash(clause.literals()))
+                .or_default()
+                .push(index);
+        }
+    }
+
+    /// Returns an irredundant clause with exactly the given sorted and deduplicated literals
+    pub fn find_irredundant_clause(&self, literals: &[Literal]) -> Option<ClauseIndex> {
+        let candidates = self.lookup.get(&literals_hash(literals))?;
+        candidates.iter().copied().find(|&index| {
+            let clause = &self.clauses[index];
+            !clause.header().learned
+                && clause.literals().len() == literals.len()
+                && clause
+                    .literals()
+                    .iter()
+                    .all(|l| literals.binary_search(l).is_ok())
+        })
     }
 
     pub fn find_clause_mut(&mut self, index: ClauseIndex) -> &mut Clause {
@@ -25,4 +96,52 @@ impl LongClauses {
     pub fn literals(&self, index: ClauseIndex) -> &[Literal] {
         self.clauses[index].literals()
     }
+
+    /// Removes the clause from the lookup by its literals
+    fn unlink(&mut self, index: ClauseIndex) {
+        let hash = literals_hash(self.clauses[index].literals());
+        if let Some(indices) = self.lookup.get_mut(&hash) {
+            indices.retain(|&i| i != index);
+            if indices.is_empty() {
+                self.lookup.remove(&hash);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lits(literals: &[i32]) -> Vec<Literal> {
+        literals.iter().map(|l| Literal::from_dimacs(*l)).collect()
+    }
+
+    #[test]
+    fn test_find_irredundant_clause() {
+        let mut clauses = LongClauses::default();
+        clauses.add_clause(&lits(&[-1, 2, 3]));
+        clauses.add_clause(&lits(&[3, 1, 2, 4]));
+        clauses.add_clause(&lits(&[2, 3, 4]));
+
+        assert_eq!(
+            clauses.find_irredundant_clause(&lits(&[1, 2, 3, 4])),
+            Some(1)
+        );
+        assert_eq!(clauses.find_irredundant_clause(&lits(&[1, 2, 3])), None);
+
+        // the lookup follows strengthening, deletion and garbage collection
+        clauses.strengthen_clause(1, &lits(&[3, 1, 2]));
+        assert_eq!(clauses.find_irredundant_clause(&lits(&[1, 2, 3])), Some(1));
+        assert_eq!(clauses.find_irredundant_clause(&lits(&[1, 2, 3, 4])), None);
+        clauses.delete_clause(0);
+        clauses.collect_garbage();
+        assert_eq!(clauses.find_irredundant_clause(&lits(&[1, 2, 3])), Some(0));
+        assert_eq!(clauses.find_irredundant_clause(&lits(&[2, 3, 4])), Some(1));
+        clauses.delete_clause(1);
+        assert_eq!(clauses.find_irredundant_clause(&lits(&[2, 3, 4])), None);
+
+        clauses.find_clause_mut(0).header_mut().learned = true;
+        assert_eq!(clauses.find_irredundant_clause(&lits(&[1, 2, 3])), None);
+    }
 }
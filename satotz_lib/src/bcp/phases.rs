@@ -0,0 +1,151 @@
+use crate::bcp::trail::Step;
+use crate::literal::{Literal, Variable};
+use crate::resize::Resize;
+
+/// Phase of every variable before it was assigned for the first time
+pub const ORIGINAL_PHASE: bool = true;
+
+/// Polarities of decisions.
+///
+/// Saved phases are the last values of the variables (phase saving, Pipatsrisawat and Darwiche
+/// 2007). Target phases are the values of the longest conflict-free trail since the last restart,
+/// best phases the values of the longest conflict-free trail since the last rephase (Biere and
+/// Fleury 2020). Both are updated by `trail::backtrack` before the values are reset, but not by
+/// the backtracks of simplification passes. Phases set by the user override all of them.
+#[derive(Default, Debug)]
+pub struct Phases {
+    /// the last value of every variable, saved when it is unassigned
+    pub saved: Vec<bool>,
+    pub target: Vec<bool>,
+    target_len: usize,
+    pub best: Vec<bool>,
+    best_len: usize,
+    /// phases set by the user
+    pub forced: Vec<Option<bool>>,
+}
+
+impl Resize for Phases {
+    fn resize(&mut self, var_count: usize) {
+        self.saved.resize(var_count, ORIGINAL_PHASE);
+        self.target.resize(var_count, ORIGINAL_PHASE);
+        self.best.resize(var_count, ORIGINAL_PHASE);
+        self.forced.resize(var_count, None);
+    }
+}
+
+impl Phases {
+    /// Returns the literal of the variable in its target or saved phase, target phases are useful
+    /// in long stretches without restarts
+    pub fn decision(&self, variable: Variable, target: bool) -> Literal {
+        let phases = if target { &self.target } else { &self.saved };
+        let phase = self.forced[variable.as_index()].unwrap_or(phases[variable.as_index()]);
+        Literal::from_variable(&variable, phase)
+    }
+
+    /// Returns the literal of the decision with the phase set by the user, if there is one
+    pub fn force(&self, literal: Literal) -> Literal {
+        let variable = literal.variable();
+        match self.forced[variable.as_index()] {
+            Some(phase) => Literal::from_variable(&variable, phase),
+            None => literal,
+        }
+    }
+
+    /// Updates the target and best phases with a conflict-free prefix of the trail, has to be
+    /// called before the trail is shortened
+    pub fn save_trail(&mut self, conflict_free: &[Step]) {
+        if conflict_free.len() > self.target_len {
+            self.target_len = conflict_free.len();
+            copy_values(&mut self.target, conflict_free);
+        }
+        if conflict_free.len() > self.best_len {
+            self.best_len = conflict_free.len();
+            copy_values(&mut self.best, conflict_free);
+        }
+    }
+
+    /// Forgets the length of the target trail after a restart
+    pub fn reset_target(&mut self) {
+        self.target_len = 0;
+    }
+
+    /// Forgets the length of the best trail after a rephase
+    pub fn reset_best(&mut self) {
+        self.best_len = 0;
+    }
+}
+
+fn copy_values(phases: &mut [bool], steps: &[Step]) {
+    for step in steps {
+        let literal = step.assigned_literal;
+        phases[literal.variable().as_index()] = literal.is_positive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bcp::propagate;
+    use crate::bcp::trail::{backtrack, backtrack_without_phases, decide_and_assign};
+    use crate::bcp::BcpContext;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_save_phases_on_backtrack() {
+        let cnf = CNF::from_dimacs("1 2 3 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        decide_and_assign(&mut bcp, Literal::from_dimacs(-1));
+        decide_and_assign(&mut bcp, Literal::from_dimacs(-2));
+        propagate(&mut bcp).unwrap();
+        backtrack(&mut bcp, 0);
+
+        assert_eq!(bcp.phases.saved, vec![false, false, true, true]);
+        assert_eq!(bcp.phases.target, vec![false, false, true, true]);
+        assert_eq!(bcp.phases.best, vec![false, false, true, true]);
+
+        // a shorter trail does not replace the target and best phases
+        decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+        propagate(&mut bcp).unwrap();
+        backtrack(&mut bcp, 0);
+        assert_eq!(bcp.phases.saved, vec![true, false, true, true]);
+        assert_eq!(bcp.phases.target, vec![false, false, true, true]);
+
+        // unless the target was reset by a restart
+        bcp.phases.reset_target();
+        decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+        propagate(&mut bcp).unwrap();
+        backtrack(&mut bcp, 0);
+        assert_eq!(bcp.phases.target, vec![true, false, true, true]);
+        assert_eq!(bcp.phases.best, vec![false, false, true, true]);
+    }
+
+    #[test]
+    fn test_backtrack_without_phases() {
+        let cnf = CNF::from_dimacs("1 2 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        decide_and_assign(&mut bcp, Literal::from_dimacs(-1));
+        propagate(&mut bcp).unwrap();
+        backtrack_without_phases(&mut bcp, 0);
+        assert_eq!(bcp.phases.saved, vec![true, true]);
+        assert_eq!(bcp.phases.target, vec![true, true]);
+        assert_eq!(bcp.phases.best, vec![true, true]);
+    }
+
+    #[test]
+    fn test_conflicting_level_is_not_saved() {
+        let cnf = CNF::from_dimacs("1 2 0\n1 -2 0\n3 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        decide_and_assign(&mut bcp, Literal::from_dimacs(-3));
+        propagate(&mut bcp).unwrap();
+        decide_and_assign(&mut bcp, Literal::from_dimacs(-1));
+        assert!(propagate(&mut bcp).is_err());
+        backtrack(&mut bcp, 0);
+
+        // only the first level is conflict-free
+        assert_eq!(bcp.phases.target, vec![true, true, false, true]);
+        assert!(!bcp.phases.saved[0]);
+    }
+}
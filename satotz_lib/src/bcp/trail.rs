@@ -22,7 +22,7 @@ pub enum Reason {
 
 impl Reason {
     /// Returns the falsified literals that caused the propagation
-    pub fn causing_literals<'a>(&'a self, context: &'a BcpContext) -> &[Literal] {
+    pub fn causing_literals<'a>(&'a self, context: &'a BcpContext) -> &'a [Literal] {
         match self {
             Reason::SolverDecision | Reason::Unit => &[],
             Reason::Binary(literal) => std::slice::from_ref(literal),
@@ -47,11 +47,25 @@ pub struct Trail {
     step_index_by_var: Vec<StepIndex>,
     propagated: usize,
     decisions: Vec<u32>,
+    /// length of the prefix of the trail that is known to be free of conflicts
+    conflict_free: usize,
+    /// variables unassigned by backtracking since the decision heuristic was last updated
+    unassigned: Vec<Variable>,
+    /// maps a variable to true if it is in `unassigned`
+    pending_unassigned: Vec<bool>,
+    /// shortest length of the trail since the last call of `take_backtracked_length`
+    backtracked_length: usize,
+    /// number of analyzed conflicts, the clock of the assignment time stamps
+    conflicts: u64,
+    /// maps a variable to the number of conflicts before it was last assigned
+    assigned_at: Vec<u64>,
 }
 
 impl Resize for Trail {
     fn resize(&mut self, var_count: usize) {
         self.step_index_by_var.resize(var_count, 0);
+        self.pending_unassigned.resize(var_count, false);
+        self.assigned_at.resize(var_count, 0);
     }
 }
 
@@ -62,6 +76,12 @@ impl Default for Trail {
             step_index_by_var: Default::default(),
             propagated: 0,
             decisions: vec![0],
+            conflict_free: 0,
+            unassigned: vec![],
+            pending_unassigned: vec![],
+            backtracked_length: 0,
+            conflicts: 0,
+            assigned_at: vec![],
         }
     }
 }
@@ -89,8 +109,60 @@ impl Trail {
         &self.steps
     }
 
+    /// Returns the literal decided on the given decision level, which is above the top level
+    pub fn decision(&self, decision_level: u32) -> Literal {
+        self.steps[self.decisions[decision_level as usize] as usize].assigned_literal
+    }
+
+    /// Marks the whole trail as conflict-free after propagation reached a fixpoint, or only the
+    /// levels below the current one after a conflict
+    pub fn update_conflict_free(&mut self, conflict: bool) {
+        self.conflict_free = if conflict {
+            self.decisions[self.current_decision_level() as usize] as usize
+        } else {
+            self.steps.len()
+        };
+    }
+
+    /// Returns the variables unassigned by backtracking since the last call, each at most once
+    pub fn take_unassigned(&mut self) -> Vec<Variable> {
+        for variable in &self.unassigned {
+            self.pending_unassigned[variable.as_index()] = false;
+        }
+        std::mem::take(&mut self.unassigned)
+    }
+
+    /// Returns the shortest length of the trail since the last call, the steps up to it were not
+    /// changed since then
+    pub fn take_backtracked_length(&mut self) -> usize {
+        std::mem::replace(&mut self.backtracked_length, self.steps.len())
+    }
+
+    /// Replaces the clause reasons of top level assignments, which are never used in conflict
+    /// analysis, so that the clauses can be removed
+    pub fn forget_top_level_reasons(&mut self) {
+        for step in self.steps.iter_mut() {
+            if step.decision_level == TOP_DECISION_LEVEL {
+                step.reason = Reason::Unit;
+            }
+        }
+    }
+
+    /// Advances the clock of the assignment time stamps by a conflict
+    pub fn count_conflict(&mut self) {
+        self.conflicts += 1;
+    }
+
+    pub fn conflicts(&self) -> u64 {
+        self.conflicts
+    }
+
+    /// Returns the number of conflicts before the variable was last assigned
+    pub fn assigned_at(&self, variable: Variable) -> u64 {
+        self.assigned_at[variable.as_index()]
+    }
+
     /// Returns the step where given variable was assigned
-    #[allow(dead_code)] // used for tests
     pub fn get_step_for_variable(&self, var: Variable) -> &Step {
         &self.steps[self.step_index(var)]
     }
@@ -98,7 +170,9 @@ impl Trail {
 
 /// adds given step to the trail, assigning the literal
 pub fn assign(values: &mut VariableAssignment, trail: &mut Trail, step: Step) {
-    trail.step_index_by_var[step.assigned_literal.variable().as_index()] = trail.steps.len();
+    let variable = step.assigned_literal.variable().as_index();
+    trail.step_index_by_var[variable] = trail.steps.len();
+    trail.assigned_at[variable] = trail.conflicts;
     values.assign_true(step.assigned_literal);
     trail.steps.push(step);
 }
@@ -116,6 +190,16 @@ pub fn decide_and_assign(bcp: &mut BcpContext, literal: Literal) {
 
 /// backtracks to given decision level, undoing assignments of a higher level
 pub fn backtrack(bcp: &mut BcpContext, decision_level: u32) {
+    undo_levels(bcp, decision_level, true);
+}
+
+/// Backtracks like `backtrack` without saving phases, for the decisions of simplification passes
+/// like probing, which do not reflect the search
+pub fn backtrack_without_phases(bcp: &mut BcpContext, decision_level: u32) {
+    undo_levels(bcp, decision_level, false);
+}
+
+fn undo_levels(bcp: &mut BcpContext, decision_level: u32, save_phases: bool) {
     // backtrack target must be lower than current decision level
     assert!(decision_level < bcp.trail.current_decision_level());
 
@@ -123,10 +207,24 @@ pub fn backtrack(bcp: &mut BcpContext, decision_level: u32) {
     let decision_level = decision_level as usize;
     let target_trail_len = bcp.trail.decisions[decision_level + 1] as usize;
 
-    // Undo the assignments
+    // the phases are updated before the values of the trail are reset to unknown
+    let conflict_free = bcp.trail.conflict_free;
+    if save_phases {
+        bcp.phases.save_trail(&bcp.trail.steps[..conflict_free]);
+    }
+    bcp.trail.conflict_free = conflict_free.min(target_trail_len);
+    bcp.trail.backtracked_length = bcp.trail.backtracked_length.min(target_trail_len);
+
+    // Undo the assignments, saving their values as phases for later decisions
     for step in bcp.trail.steps.drain(target_trail_len..) {
         let variable = step.assigned_literal.variable();
         bcp.assignment.assign_unknown(variable);
+        if save_phases {
+            bcp.phases.saved[variable.as_index()] = step.assigned_literal.is_positive();
+        }
+        if !std::mem::replace(&mut bcp.trail.pending_unassigned[variable.as_index()], true) {
+            bcp.trail.unassigned.push(variable);
+        }
     }
 
     // remove from graph
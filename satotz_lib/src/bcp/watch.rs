@@ -1,18 +1,35 @@
+use crate::assignment::VariableAssignment;
 use crate::clause::ClauseIndex;
 use crate::literal::Literal;
 use crate::resize::Resize;
+use std::collections::HashMap;
 
-#[derive(Debug, Copy, Clone)]
-pub struct LiteralWatch {
-    pub clause_index: ClauseIndex,
-    pub satisfying_literal: Literal,
+/// Entry in the watchlist of a literal, tagged by the kind of the watched clause so that
+/// propagation visits all clauses of a literal in a single pass
+/// Clause indices are stored as `u32`, which keeps a watch at 16 bytes
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Watch {
+    /// binary clause, which is stored only in the watchlists of its two literals
+    Binary(Literal),
+    /// ternary clause with both other literals inline, so that the clause only needs to be
+    /// accessed to propagate it or to report a conflict
+    Ternary(u32, [Literal; 2]),
+    /// long clause with a blocking literal, if the blocker is true the clause is satisfied
+    Long(u32, Literal),
 }
 
-/// For every literal, keeps a list of clauses watched by this literal
-/// The watched literals will always be the first two, at index 0 and 1
+/// For every literal, keeps a list of the clauses watched by this literal
+/// Binary and ternary clauses are watched by all their literals, long clauses by their first two
+/// literals, at index 0 and 1
 #[derive(Default, Debug)]
 pub struct Watchlists {
-    watches_by_lit: Vec<Vec<LiteralWatch>>,
+    watches_by_lit: Vec<Vec<Watch>>,
+    /// number of occurrences of every binary clause, by its sorted literals
+    binary_counts: HashMap<[Literal; 2], u32>,
+}
+
+fn sorted([a, b]: [Literal; 2]) -> [Literal; 2] {
+    [a.min(b), a.max(b)]
 }
 
 impl Resize for Watchlists {
@@ -22,32 +39,131 @@ impl Resize for Watchlists {
 }
 
 impl Watchlists {
-    /// create new watches for a clause and add them
-    pub fn watch_clause(&mut self, clause_index: ClauseIndex, literals: [Literal; 2]) {
+    /// create new watches for a clause with at least three literals and add them
+    pub fn watch_clause(&mut self, clause_index: ClauseIndex, literals: &[Literal]) {
+        let clause_index = clause_index as u32;
+        if let [a, b, c] = *literals {
+            for (watched_literal, other_literals) in [(a, [b, c]), (b, [a, c]), (c, [a, b])] {
+                self.add_watch(
+                    watched_literal,
+                    Watch::Ternary(clause_index, other_literals),
+                );
+            }
+            return;
+        }
+
         for i in 0..2 {
-            let watched_literal = literals[i];
-            let satisfying_literal = literals[i ^ 1];
-            let watch = LiteralWatch {
-                clause_index,
-                satisfying_literal,
-            };
-            self.add_watch(watched_literal, watch);
+            self.add_watch(literals[i], Watch::Long(clause_index, literals[i ^ 1]));
+        }
+    }
+
+    /// Removes the watches of a clause with at least three literals, which are given in the
+    /// order they are watched in
+    pub fn unwatch_clause(&mut self, clause_index: ClauseIndex, literals: &[Literal]) {
+        let watched = if literals.len() == 3 {
+            literals
+        } else {
+            &literals[..2]
+        };
+        let clause_index = clause_index as u32;
+        for literal in watched {
+            self.watches_by_lit[literal.as_index()].retain(|w| match *w {
+                Watch::Ternary(index, _) | Watch::Long(index, _) => index != clause_index,
+                Watch::Binary(_) => true,
+            });
         }
     }
 
-    pub fn add_watch(&mut self, lit: Literal, watch: LiteralWatch) {
+    pub fn add_watch(&mut self, lit: Literal, watch: Watch) {
         self.watches_by_lit[lit.as_index()].push(watch);
     }
 
+    /// Removes the watches of all ternary and long clauses, binary clauses are kept
+    pub fn clear_clause_watches(&mut self) {
+        self.watches_by_lit
+            .iter_mut()
+            .for_each(|w| w.retain(|watch| matches!(watch, Watch::Binary(_))));
+    }
+
     /// Take ownership of a literals watchlist
-    pub fn take_watchlist(&mut self, lit: Literal) -> Vec<LiteralWatch> {
+    pub fn take_watchlist(&mut self, lit: Literal) -> Vec<Watch> {
         std::mem::take(&mut self.watches_by_lit[lit.as_index()])
     }
 
     /// Return ownership of a literals watchlist
-    pub fn place_watchlist(&mut self, lit: Literal, watchlist: Vec<LiteralWatch>) {
+    pub fn place_watchlist(&mut self, lit: Literal, watchlist: Vec<Watch>) {
         self.watches_by_lit[lit.as_index()] = watchlist;
     }
+
+    pub fn add_binary_clause(&mut self, clause: [Literal; 2]) {
+        for i in 0..2 {
+            self.add_watch(clause[i], Watch::Binary(clause[i ^ 1]));
+        }
+        *self.binary_counts.entry(sorted(clause)).or_default() += 1;
+    }
+
+    /// Removes one occurrence of the binary clause
+    pub fn remove_binary_clause(&mut self, clause: [Literal; 2]) {
+        for i in 0..2 {
+            self.remove_binary_watch(clause[i], clause[i ^ 1]);
+        }
+        self.uncount_binary_clause(clause);
+    }
+
+    /// Removes all binary clauses containing the literal and returns their other literals
+    pub fn remove_binary_clauses_with(&mut self, literal: Literal) -> Vec<Literal> {
+        let others = self.binary_clauses(literal).collect::<Vec<_>>();
+        self.watches_by_lit[literal.as_index()].retain(|w| !matches!(w, Watch::Binary(_)));
+        for &other in &others {
+            self.remove_binary_watch(other, literal);
+            self.uncount_binary_clause([literal, other]);
+        }
+        others
+    }
+
+    fn uncount_binary_clause(&mut self, clause: [Literal; 2]) {
+        let clause = sorted(clause);
+        if let Some(count) = self.binary_counts.get_mut(&clause) {
+            *count -= 1;
+            if *count == 0 {
+                self.binary_counts.remove(&clause);
+            }
+        }
+    }
+
+    fn remove_binary_watch(&mut self, literal: Literal, other: Literal) {
+        let list = &mut self.watches_by_lit[literal.as_index()];
+        if let Some(position) = list.iter().position(|w| *w == Watch::Binary(other)) {
+            list.swap_remove(position);
+        }
+    }
+
+    /// Returns the other literals of all binary clauses that contain the given literal
+    pub fn binary_clauses(&self, literal: Literal) -> impl Iterator<Item = Literal> + '_ {
+        self.watches_by_lit[literal.as_index()]
+            .iter()
+            .filter_map(|w| match *w {
+                Watch::Binary(other) => Some(other),
+                _ => None,
+            })
+    }
+
+    /// Returns true if the binary clause is already stored
+    pub fn contains_binary_clause(&self, clause: [Literal; 2]) -> bool {
+        self.binary_counts.contains_key(&sorted(clause))
+    }
+
+    /// Returns the number of unresolved binary clauses with this literal
+    #[allow(dead_code)] // used for tests
+    pub fn unresolved_binary_clauses_count(
+        &self,
+        literal: Literal,
+        assignment: &VariableAssignment,
+    ) -> u32 {
+        self.binary_clauses(literal)
+            .filter(|other| assignment.literal_is_unknown(*other))
+            .count() as u32
+    }
 }
 
 #[cfg(test)]
@@ -57,10 +173,81 @@ mod tests {
     #[test]
     fn test_watchlists_from_long_clause() {
         let mut watches = Watchlists::default();
-        watches.resize(2);
-        watches.watch_clause(0, [Literal::from_dimacs(1), Literal::from_dimacs(2)]);
+        watches.resize(4);
+        let literals = [1, 2, 3, 4].map(Literal::from_dimacs);
+        watches.watch_clause(0, &literals);
         let list = watches.take_watchlist(Literal::from_dimacs(1));
-        assert_eq!(list.len(), 1);
-        assert_eq!(list[0].clause_index, 0);
+        assert_eq!(list, vec![Watch::Long(0, literals[1])]);
+        assert!(watches.take_watchlist(Literal::from_dimacs(3)).is_empty());
+    }
+
+    #[test]
+    fn test_watch_ternary_clause() {
+        let mut watches = Watchlists::default();
+        watches.resize(3);
+        let literals = [1, -2, 3].map(Literal::from_dimacs);
+        watches.watch_clause(0, &literals);
+
+        let list = watches.take_watchlist(Literal::from_dimacs(-2));
+        assert_eq!(list, vec![Watch::Ternary(0, [literals[0], literals[2]])]);
+        assert!(watches.take_watchlist(Literal::from_dimacs(2)).is_empty());
+
+        watches.unwatch_clause(0, &literals);
+        assert!(watches.take_watchlist(literals[0]).is_empty());
+    }
+
+    #[test]
+    fn test_remove_one_binary_clause() {
+        let mut watches = Watchlists::default();
+        watches.resize(3);
+        let [a, b, c] = [1, -2, 3].map(Literal::from_dimacs);
+        watches.add_binary_clause([a, b]);
+        watches.add_binary_clause([a, b]);
+        watches.add_binary_clause([a, c]);
+
+        watches.remove_binary_clause([b, a]);
+        assert!(watches.contains_binary_clause([a, b]));
+        assert!(watches.contains_binary_clause([b, a]));
+        watches.remove_binary_clause([a, b]);
+        assert!(!watches.contains_binary_clause([a, b]));
+        assert!(!watches.contains_binary_clause([b, a]));
+        assert_eq!(watches.binary_clauses(a).collect::<Vec<_>>(), vec![c]);
+    }
+
+    #[test]
+    fn test_unresolved_binary_clauses_count() {
+        let mut watches = Watchlists::default();
+        watches.resize(4);
+        let [a, b, c, d] = [1, 2, 3, 4].map(Literal::from_dimacs);
+        watches.add_binary_clause([a, b]);
+        watches.add_binary_clause([a, c]);
+        watches.add_binary_clause([a, d]);
+        watches.watch_clause(0, &[a, b, c]);
+
+        let mut assignment = VariableAssignment::default();
+        assignment.resize(4);
+        assert_eq!(watches.unresolved_binary_clauses_count(a, &assignment), 3);
+        assignment.assign_true(!b);
+        assignment.assign_true(c);
+        assert_eq!(watches.unresolved_binary_clauses_count(a, &assignment), 1);
+    }
+
+    #[test]
+    fn test_binary_clauses_survive_clearing() {
+        let mut watches = Watchlists::default();
+        watches.resize(4);
+        let [a, b, c, d] = [1, 2, 3, 4].map(Literal::from_dimacs);
+        watches.add_binary_clause([a, b]);
+        watches.add_binary_clause([a, c]);
+        watches.watch_clause(0, &[a, b, c, d]);
+
+        watches.watch_clause(1, &[b, c, d]);
+        watches.clear_clause_watches();
+        assert_eq!(watches.binary_clauses(a).collect::<Vec<_>>(), vec![b, c]);
+        assert!(watches.take_watchlist(d).is_empty());
+
+        assert_eq!(watches.remove_binary_clauses_with(a), vec![b, c]);
+        assert!(!watches.contains_binary_clause([b, a]));
+        assert!(watches.take_watchlist(c).is_empty());
     }
 }
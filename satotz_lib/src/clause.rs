@@ -3,12 +3,50 @@ use crate::literal::Literal;
 /// Type wrapper for readability. The index of the clause in [bcp::long_clauses].
 pub type ClauseIndex = usize;
 
+/// Learned clauses with at most this glue are kept forever
+pub const CORE_GLUE: u32 = 2;
+
+/// Learned clauses with at most this glue are kept while they are used
+pub const TIER2_GLUE: u32 = 6;
+
+/// Classes of learned clauses by glue, which decide how long a clause is kept
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Tier {
+    Core,
+    Tier2,
+    Local,
+}
+
 /// Contains metadata for a clause
-#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Hash)]
-pub struct ClauseMeta {}
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct ClauseMeta {
+    /// the clause was derived during conflict analysis
+    pub learned: bool,
+    /// literal block distance, the lowest number of distinct decision levels of the literals when
+    /// the clause was learned or took part in conflict analysis
+    pub glue: u32,
+    /// the clause was removed from the formula, its watches are dropped lazily
+    pub deleted: bool,
+    /// the clause took part in conflict analysis since the last reduction
+    pub used: bool,
+    /// bumped whenever the clause takes part in conflict analysis, decays over time
+    pub activity: f32,
+}
+
+impl ClauseMeta {
+    pub fn tier(&self) -> Tier {
+        if self.glue <= CORE_GLUE {
+            Tier::Core
+        } else if self.glue <= TIER2_GLUE {
+            Tier::Tier2
+        } else {
+            Tier::Local
+        }
+    }
+}
 
 /// Representation of one long clause (3+ literals) in the propagation datastructure [bcp::long_clauses]
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Clause {
     header: ClauseMeta,
     literals: Box<[Literal]>,
@@ -3,7 +3,7 @@ use crate::literal::{Literal, Variable};
 use crate::parse::parse_dimacs_cnf;
 use std::path::PathBuf;
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct CNF {
     clauses: Vec<Clause>,
     variables: Vec<Variable>,
@@ -55,6 +55,27 @@ impl CNF {
         }
     }
 
+    /// Extends the variables of the formula to at least the given count, e.g. to include variables
+    /// that do not occur in any clause
+    pub fn with_variable_count(mut self, count: usize) -> CNF {
+        let current = self.variables.len() as u32;
+        self.variables
+            .extend((current..count as u32).map(Variable::from_index));
+        self
+    }
+
+    /// Writes the formula in dimacs cnf format
+    pub fn to_dimacs(&self) -> String {
+        let mut output = format!("p cnf {} {}\n", self.variables.len(), self.clauses.len());
+        for clause in &self.clauses {
+            for literal in clause.literals() {
+                output.push_str(&format!("{} ", literal));
+            }
+            output.push_str("0\n");
+        }
+        output
+    }
+
     pub fn variable_count(&self) -> usize {
         self.variables.len()
     }
@@ -92,7 +113,7 @@ mod tests {
 
     #[test]
     fn test_variable_count() {
-        let cnf = CNF::from_clauses(&vec![vec![1, 2, 3], vec![10]]);
+        let cnf = CNF::from_clauses(&[vec![1, 2, 3], vec![10]]);
 
         // the formula should contain all variables. also variables between 3 and 10
         assert_eq!(10, cnf.variables.len());
@@ -101,4 +122,15 @@ mod tests {
             cnf.variables.iter().map(|v| v.index()).collect::<Vec<_>>()
         );
     }
+
+    #[test]
+    fn test_dimacs_roundtrip() {
+        let cnf = CNF::from_clauses(&[vec![1, -2], vec![], vec![3]]).with_variable_count(4);
+        let output = cnf.to_dimacs();
+        assert_eq!(output, "p cnf 4 3\n1 -2 0\n0\n3 0\n");
+
+        let parsed = CNF::from_dimacs(&output);
+        assert_eq!(parsed.clauses().len(), 3);
+        assert_eq!(parsed.variable_count(), 3);
+    }
 }
@@ -4,7 +4,13 @@ mod bcp;
 mod clause;
 pub mod cnf;
 mod literal;
+pub mod local_search;
 mod parse;
+mod proof;
+mod random;
+pub mod reconstruct;
 mod resize;
 mod search;
+mod simplify;
 pub mod solver;
+pub mod statistics;
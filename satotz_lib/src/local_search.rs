@@ -0,0 +1,364 @@
+use crate::cnf::CNF;
+use crate::literal::{Literal, Variable};
+use crate::random::XorShift;
+
+/// Constant of the polynomial break value distribution, tuned for random 3-SAT
+const BREAK_CONSTANT: f64 = 2.06;
+
+/// Offset of the polynomial break value distribution
+const BREAK_EPSILON: f64 = 0.9;
+
+/// Break values above this share the probability of the largest one
+const MAX_BREAK_VALUE: usize = 64;
+
+/// Seed used if none is given
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+/// ProbSAT stochastic local search (Balint and Schöning 2012).
+///
+/// Starting from a random assignment, a random falsified clause is picked in every step, and one
+/// of its variables is flipped with a probability proportional to `(eps + break)^-cb`, where the
+/// break value of a variable is the number of clauses that become falsified by flipping it.
+///
+/// Break values are updated incrementally: every clause stores its number of true literals and
+/// the xor of their codes, which is the code of the only true literal if there is exactly one.
+/// Local search can only find models, it never proves unsatisfiability.
+#[derive(Debug)]
+pub struct LocalSearch {
+    /// sorted literals of all non-tautological clauses
+    clauses: Vec<Vec<Literal>>,
+    /// maps a literal to the clauses containing it
+    occurrences: Vec<Vec<usize>>,
+    values: Vec<bool>,
+    true_counts: Vec<u32>,
+    true_literals_xor: Vec<u32>,
+    break_values: Vec<u32>,
+    /// falsified clauses
+    unsat_clauses: Vec<usize>,
+    /// maps a clause to its position in `unsat_clauses`
+    unsat_positions: Vec<usize>,
+    /// probability weight of every break value
+    weights: Vec<f64>,
+    has_empty_clause: bool,
+    rng: XorShift,
+}
+
+impl LocalSearch {
+    pub fn from_cnf(cnf: &CNF) -> LocalSearch {
+        let clauses = cnf
+            .clauses()
+            .iter()
+            .map(|c| c.literals().to_vec())
+            .collect::<Vec<_>>();
+        LocalSearch::from_clauses(cnf.variable_count(), &clauses)
+    }
+
+    pub fn from_clauses(variable_count: usize, clauses: &[Vec<Literal>]) -> LocalSearch {
+        let mut search = LocalSearch {
+            clauses: vec![],
+            occurrences: vec![vec![]; variable_count * 2],
+            values: vec![false; variable_count],
+            true_counts: vec![],
+            true_literals_xor: vec![],
+            break_values: vec![0; variable_count],
+            unsat_clauses: vec![],
+            unsat_positions: vec![],
+            weights: (0..=MAX_BREAK_VALUE)
+                .map(|b| (BREAK_EPSILON + b as f64).powf(-BREAK_CONSTANT))
+                .collect(),
+            has_empty_clause: false,
+            rng: XorShift::new(DEFAULT_SEED),
+        };
+
+        for clause in clauses {
+            let mut literals = clause.clone();
+            literals.sort_unstable();
+            literals.dedup();
+
+            if literals.is_empty() {
+                search.has_empty_clause = true;
+            } else if !literals.windows(2).any(|w| w[0] == !w[1]) {
+                for literal in &literals {
+                    search.occurrences[literal.as_index()].push(search.clauses.len());
+                }
+                search.clauses.push(literals);
+            }
+        }
+
+        search
+    }
+
+    /// Uses the given seed for the random assignment and the choice of flipped variables
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = XorShift::new(seed);
+        self
+    }
+
+    /// Searches for a model with at most `flip_limit` flips. Returns the model as one literal per
+    /// variable, or `None` if no model was found.
+    pub fn solve(&mut self, flip_limit: u64) -> Option<Vec<Literal>> {
+        if self.has_empty_clause {
+            return None;
+        }
+
+        for index in 0..self.values.len() {
+            self.values[index] = self.rng.next() & 1 == 1;
+        }
+        self.initialize();
+
+        for _ in 0..flip_limit {
+            if self.unsat_clauses.is_empty() {
+                break;
+            }
+            self.step();
+        }
+
+        if !self.unsat_clauses.is_empty() {
+            return None;
+        }
+
+        Some(
+            self.values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| Literal::from_index(index as u32, *value))
+                .collect(),
+        )
+    }
+
+    /// Searches for an assignment with few falsified clauses with at most `flip_limit` flips,
+    /// starting from the given values. Returns the values with the fewest falsified clauses.
+    pub fn walk(&mut self, initial: &[bool], flip_limit: u64) -> Vec<bool> {
+        if self.has_empty_clause {
+            return initial.to_vec();
+        }
+
+        self.values.copy_from_slice(initial);
+        self.initialize();
+
+        let mut best = self.values.clone();
+        let mut best_unsat = self.unsat_clauses.len();
+
+        for _ in 0..flip_limit {
+            if best_unsat == 0 {
+                break;
+            }
+            self.step();
+            if self.unsat_clauses.len() < best_unsat {
+                best_unsat = self.unsat_clauses.len();
+                best.copy_from_slice(&self.values);
+            }
+        }
+
+        best
+    }
+
+    /// Flips a variable of a random falsified clause
+    fn step(&mut self) {
+        let clause = self.unsat_clauses[self.rng.below(self.unsat_clauses.len())];
+        let variable = self.pick_variable(clause);
+        self.flip(variable);
+    }
+
+    /// Computes the counters from scratch for the current values
+    fn initialize(&mut self) {
+        self.true_counts = vec![0; self.clauses.len()];
+        self.true_literals_xor = vec![0; self.clauses.len()];
+        self.unsat_positions = vec![0; self.clauses.len()];
+        self.unsat_clauses.clear();
+        self.break_values.iter_mut().for_each(|b| *b = 0);
+
+        for (index, clause) in self.clauses.iter().enumerate() {
+            for literal in clause {
+                if self.values[literal.variable().as_index()] == literal.is_positive() {
+                    self.true_counts[index] += 1;
+                    self.true_literals_xor[index] ^= literal.as_code();
+                }
+            }
+
+            match self.true_counts[index] {
+                0 => {
+                    self.unsat_positions[index] = self.unsat_clauses.len();
+                    self.unsat_clauses.push(index);
+                }
+                1 => {
+                    let critical = Literal::from_code(self.true_literals_xor[index] as usize);
+                    self.break_values[critical.variable().as_index()] += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Picks a variable of a falsified clause according to the break value distribution
+    fn pick_variable(&mut self, clause: usize) -> Variable {
+        let weights = self.clauses[clause]
+            .iter()
+            .map(|l| {
+                let break_value = self.break_values[l.variable().as_index()] as usize;
+                self.weights[break_value.min(MAX_BREAK_VALUE)]
+            })
+            .collect::<Vec<_>>();
+
+        let mut threshold = self.rng.unit() * weights.iter().sum::<f64>();
+        for (literal, weight) in self.clauses[clause].iter().zip(&weights) {
+            if threshold < *weight {
+                return literal.variable();
+            }
+            threshold -= weight;
+        }
+
+        // rounding errors
+        self.clauses[clause].last().unwrap().variable()
+    }
+
+    fn flip(&mut self, variable: Variable) {
+        let value = !self.values[variable.as_index()];
+        self.values[variable.as_index()] = value;
+
+        let true_literal = Literal::from_variable(&variable, value);
+        let false_literal = !true_literal;
+
+        for i in 0..self.occurrences[true_literal.as_index()].len() {
+            let clause = self.occurrences[true_literal.as_index()][i];
+            self.true_literals_xor[clause] ^= true_literal.as_code();
+            self.true_counts[clause] += 1;
+
+            match self.true_counts[clause] {
+                1 => {
+                    self.remove_unsat(clause);
+                    self.break_values[variable.as_index()] += 1;
+                }
+                2 => {
+                    // the previously only true literal is no longer critical
+                    let other = Literal::from_code(
+                        (self.true_literals_xor[clause] ^ true_literal.as_code()) as usize,
+                    );
+                    self.break_values[other.variable().as_index()] -= 1;
+                }
+                _ => {}
+            }
+        }
+
+        for i in 0..self.occurrences[false_literal.as_index()].len() {
+            let clause = self.occurrences[false_literal.as_index()][i];
+            self.true_literals_xor[clause] ^= false_literal.as_code();
+            self.true_counts[clause] -= 1;
+
+            match self.true_counts[clause] {
+                0 => {
+                    self.unsat_positions[clause] = self.unsat_clauses.len();
+                    self.unsat_clauses.push(clause);
+                    self.break_values[variable.as_index()] -= 1;
+                }
+                1 => {
+                    let critical = Literal::from_code(self.true_literals_xor[clause] as usize);
+                    self.break_values[critical.variable().as_index()] += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn remove_unsat(&mut self, clause: usize) {
+        let position = self.unsat_positions[clause];
+        let last = *self.unsat_clauses.last().unwrap();
+        self.unsat_clauses.swap_remove(position);
+        self.unsat_positions[last] = position;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+
+    /// Recomputes the break values and falsified clauses from scratch
+    fn assert_consistent(search: &LocalSearch) {
+        let mut break_values = vec![0; search.values.len()];
+        let mut unsat_clauses = vec![];
+
+        for (index, clause) in search.clauses.iter().enumerate() {
+            let true_literals = clause
+                .iter()
+                .filter(|l| search.values[l.variable().as_index()] == l.is_positive())
+                .collect::<Vec<_>>();
+            match *true_literals {
+                [] => unsat_clauses.push(index),
+                [critical] => break_values[critical.variable().as_index()] += 1,
+                _ => {}
+            }
+        }
+
+        let mut actual_unsat = search.unsat_clauses.clone();
+        actual_unsat.sort_unstable();
+        assert_eq!(actual_unsat, unsat_clauses);
+        assert_eq!(search.break_values, break_values);
+    }
+
+    #[test]
+    fn test_incremental_break_values() {
+        let cnf = CNF::from_file_str("../test_formulas/prime841.sat");
+        let mut search = LocalSearch::from_cnf(&cnf).with_seed(7);
+        search
+            .values
+            .iter_mut()
+            .for_each(|v| *v = search.rng.next() & 1 == 1);
+        search.initialize();
+        assert_consistent(&search);
+
+        for _ in 0..1000 {
+            let variable = Variable::from_index(search.rng.below(search.values.len()) as u32);
+            search.flip(variable);
+        }
+        assert_consistent(&search);
+    }
+
+    #[test]
+    fn test_satisfiable_formulas() {
+        for entry in fs::read_dir(PathBuf::from("../test_formulas")).unwrap() {
+            let file = entry.unwrap();
+            if !file.file_name().to_str().unwrap().contains(".sat") {
+                continue;
+            }
+
+            let cnf = CNF::from_file(file.path());
+            let mut search = LocalSearch::from_cnf(&cnf);
+            // local search is incomplete, but every model it reports has to be a model
+            if let Some(model) = search.solve(100_000) {
+                for clause in cnf.clauses() {
+                    assert!(clause.literals().iter().any(|l| model.contains(l)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_formula() {
+        let cnf = CNF::from_dimacs("1 2 0\n-1 2 0\n1 -2 0\n");
+        let model = LocalSearch::from_cnf(&cnf).solve(1000);
+        assert_eq!(
+            model,
+            Some(vec![Literal::from_dimacs(1), Literal::from_dimacs(2)])
+        );
+    }
+
+    #[test]
+    fn test_walk_keeps_best_values() {
+        let cnf = CNF::from_dimacs("1 2 0\n-1 2 0\n1 -2 0\n");
+        let mut search = LocalSearch::from_cnf(&cnf);
+        assert_eq!(search.walk(&[true, true], 1000), vec![true, true]);
+        assert_eq!(search.walk(&[false, false], 1000), vec![true, true]);
+        assert_eq!(search.walk(&[false, false], 0), vec![false, false]);
+    }
+
+    #[test]
+    fn test_unsatisfiable_formula() {
+        let cnf = CNF::from_dimacs("1 2 0\n-1 2 0\n1 -2 0\n-1 -2 0\n");
+        assert_eq!(LocalSearch::from_cnf(&cnf).solve(1000), None);
+
+        let cnf = CNF::from_dimacs("1 0\n0\n");
+        assert_eq!(LocalSearch::from_cnf(&cnf).solve(1000), None);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::literal::Literal;
+use std::fmt;
+use std::io::{self, Write};
+
+/// Writes a proof of unsatisfiability in the DRAT format.
+///
+/// Every clause that is derived by the solver is added and every clause that is removed from the
+/// formula is deleted, so that a checker can follow the clause database. Lines are written
+/// directly to the writer, which should be buffered.
+#[derive(Default)]
+pub struct Proof {
+    writer: Option<Box<dyn Write>>,
+}
+
+impl fmt::Debug for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Proof")
+            .field("enabled", &self.writer.is_some())
+            .finish()
+    }
+}
+
+impl Proof {
+    pub fn new(writer: Box<dyn Write>) -> Proof {
+        Proof {
+            writer: Some(writer),
+        }
+    }
+
+    /// Adds a derived clause to the proof
+    pub fn add_clause(&mut self, literals: &[Literal]) {
+        self.write_line("", literals);
+    }
+
+    /// Deletes a clause from the proof
+    pub fn delete_clause(&mut self, literals: &[Literal]) {
+        self.write_line("d ", literals);
+    }
+
+    fn write_line(&mut self, prefix: &str, literals: &[Literal]) {
+        if let Some(writer) = &mut self.writer {
+            write_clause(writer, prefix, literals).expect("proof io error");
+        }
+    }
+}
+
+fn write_clause(writer: &mut impl Write, prefix: &str, literals: &[Literal]) -> io::Result<()> {
+    write!(writer, "{}", prefix)?;
+    for literal in literals {
+        write!(writer, "{} ", literal)?;
+    }
+    writeln!(writer, "0")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_drat_lines() {
+        let buffer = Rc::new(RefCell::new(vec![]));
+        let mut proof = Proof::new(Box::new(SharedBuffer(buffer.clone())));
+
+        proof.add_clause(&[Literal::from_dimacs(1), Literal::from_dimacs(-2)]);
+        proof.delete_clause(&[Literal::from_dimacs(3)]);
+        proof.add_clause(&[]);
+
+        assert_eq!(
+            String::from_utf8(buffer.borrow().clone()).unwrap(),
+            "1 -2 0\nd 3 0\n0\n"
+        );
+    }
+}
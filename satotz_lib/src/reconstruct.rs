@@ -0,0 +1,369 @@
+use crate::literal::{Literal, Variable};
+use std::fmt;
+
+/// A clause removed from the formula together with the literal that satisfies it
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Entry {
+    witness: Literal,
+    clause: Vec<Literal>,
+}
+
+/// Clauses removed by variable elimination, used to extend a model of the simplified formula to a
+/// model of the original formula.
+///
+/// Written as a text file, where the first line contains the variable count of the original
+/// formula and every further line a removed clause, starting with its witness literal:
+/// ```text
+/// p stack 3
+/// 2 2 -1 0
+/// -2 -2 3 0
+/// ```
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct ReconstructionStack {
+    variable_count: usize,
+    entries: Vec<Entry>,
+    /// variables that are witnesses of removed clauses
+    eliminated: Vec<bool>,
+}
+
+impl ReconstructionStack {
+    pub fn new(variable_count: usize) -> ReconstructionStack {
+        ReconstructionStack {
+            variable_count,
+            entries: vec![],
+            eliminated: vec![],
+        }
+    }
+
+    /// The variable count of the original formula
+    pub fn variable_count(&self) -> usize {
+        self.variable_count
+    }
+
+    /// Pushes a removed clause, the witness literal has to be part of the clause
+    pub fn push(&mut self, witness: Literal, clause: &[Literal]) {
+        let index = witness.variable().as_index();
+        if index >= self.eliminated.len() {
+            self.eliminated.resize(index + 1, false);
+        }
+        self.eliminated[index] = true;
+
+        self.entries.push(Entry {
+            witness,
+            clause: clause.to_vec(),
+        });
+    }
+
+    pub fn is_eliminated(&self, variable: Variable) -> bool {
+        self.eliminated
+            .get(variable.as_index())
+            .copied()
+            .unwrap_or(false)
+    }
+
+    /// Removes the clauses with the variable as witness from the stack and returns them, so that
+    /// they can be added to the formula again
+    pub fn restore(&mut self, variable: Variable) -> Vec<Vec<Literal>> {
+        if !self.is_eliminated(variable) {
+            return vec![];
+        }
+        self.eliminated[variable.as_index()] = false;
+
+        let (restored, kept) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition::<Vec<_>, _>(|e| e.witness.variable() == variable);
+        self.entries = kept;
+
+        restored.into_iter().map(|e| e.clause).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the stack with every variable replaced by `variables[variable index]` and the given
+    /// variable count of the original formula
+    pub fn renumbered(&self, variables: &[Variable], variable_count: usize) -> ReconstructionStack {
+        let renumber = |literal: &Literal| {
+            Literal::from_variable(
+                &variables[literal.variable().as_index()],
+                literal.is_positive(),
+            )
+        };
+        let mut stack = ReconstructionStack::new(variable_count);
+        for entry in &self.entries {
+            let clause = entry.clause.iter().map(renumber).collect::<Vec<_>>();
+            stack.push(renumber(&entry.witness), &clause);
+        }
+        stack
+    }
+
+    /// Extends a model of the simplified formula, given as one literal per variable, by flipping
+    /// witness literals of falsified clauses in reverse order of their removal
+    pub fn extend(&self, model: &mut [Literal]) {
+        for entry in self.entries.iter().rev() {
+            let satisfied = entry
+                .clause
+                .iter()
+                .any(|l| model[l.variable().as_index()] == *l);
+            if !satisfied {
+                model[entry.witness.variable().as_index()] = entry.witness;
+            }
+        }
+    }
+
+    /// Extends a model of the simplified formula, given as the true literals, to a model of the
+    /// original formula. Variables missing in the model are falsified.
+    pub fn postsolve(&self, model: &[Literal]) -> Vec<Literal> {
+        let variable_count = model
+            .iter()
+            .chain(self.entries.iter().flat_map(|e| e.clause.iter()))
+            .map(|l| l.variable().as_index() + 1)
+            .max()
+            .unwrap_or(0)
+            .max(self.variable_count);
+
+        let mut extended = (0..variable_count as u32)
+            .map(|i| Literal::from_index(i, false))
+            .collect::<Vec<_>>();
+        for literal in model {
+            extended[literal.variable().as_index()] = *literal;
+        }
+
+        self.extend(&mut extended);
+        extended.truncate(self.variable_count);
+        extended
+    }
+
+    /// Parses a stack written by the `Display` implementation
+    pub fn from_text(input: &str) -> Result<ReconstructionStack, ParseError> {
+        let mut stack = ReconstructionStack::default();
+        for (index, line) in input.lines().enumerate() {
+            let line = line.trim();
+            let error = |message: &str| ParseError::new(index + 1, message);
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(count) = line.strip_prefix("p stack ") {
+                stack.variable_count = count
+                    .trim()
+                    .parse()
+                    .map_err(|_| error("invalid variable count"))?;
+                continue;
+            }
+
+            let mut literals = parse_literals(line.split_whitespace(), index + 1)?;
+            if literals.pop() != Some(0) || literals.contains(&0) {
+                return Err(error("entry not terminated by 0"));
+            }
+            let literals = literals
+                .into_iter()
+                .map(Literal::from_dimacs)
+                .collect::<Vec<_>>();
+            match literals.split_first() {
+                Some((witness, clause)) if clause.contains(witness) => stack.push(*witness, clause),
+                _ => return Err(error("witness literal not in the clause")),
+            }
+        }
+        Ok(stack)
+    }
+}
+
+/// Input that could not be parsed, with the number of the line starting at 1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    fn new(line: usize, message: &str) -> ParseError {
+        ParseError {
+            line,
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Returns whether the line is the result of a MiniSat style solver, `SAT`, `UNSAT` or `INDET`
+pub fn is_result_line(line: &str) -> bool {
+    matches!(line.trim(), "SAT" | "UNSAT" | "INDET")
+}
+
+fn parse_literals<'a>(
+    tokens: impl Iterator<Item = &'a str>,
+    line: usize,
+) -> Result<Vec<i32>, ParseError> {
+    tokens
+        .map(|token| match token.parse::<i32>() {
+            Ok(literal) if literal != i32::MIN => Ok(literal),
+            _ => Err(ParseError::new(line, &format!("invalid literal {token}"))),
+        })
+        .collect()
+}
+
+/// Parses the true literals of a model written by a solver, either as competition style `v` lines,
+/// as a list like `v [1, -2, 3]` or as a MiniSat style result file. Status and comment lines are
+/// skipped.
+pub fn parse_model(input: &str) -> Result<Vec<Literal>, ParseError> {
+    let mut model = vec![];
+    for (index, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.starts_with('s') || line.starts_with('c') || is_result_line(line) {
+            continue;
+        }
+        let tokens = line
+            .strip_prefix('v')
+            .unwrap_or(line)
+            .split(|c: char| c.is_whitespace() || c == ',' || c == '[' || c == ']')
+            .filter(|l| !l.is_empty());
+        let literals = parse_literals(tokens, index + 1)?;
+        model.extend(
+            literals
+                .into_iter()
+                .filter(|l| *l != 0)
+                .map(Literal::from_dimacs),
+        );
+    }
+    Ok(model)
+}
+
+impl fmt::Display for ReconstructionStack {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "p stack {}", self.variable_count)?;
+        for entry in &self.entries {
+            write!(f, "{} ", entry.witness)?;
+            for literal in &entry.clause {
+                write!(f, "{} ", literal)?;
+            }
+            writeln!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lits(literals: &[i32]) -> Vec<Literal> {
+        literals.iter().map(|l| Literal::from_dimacs(*l)).collect()
+    }
+
+    #[test]
+    fn test_extend_model() {
+        // variable 2 was eliminated from (2 v -1) and (-2 v 3)
+        let mut stack = ReconstructionStack::new(3);
+        stack.push(Literal::from_dimacs(2), &lits(&[2, -1]));
+        stack.push(Literal::from_dimacs(-2), &lits(&[-2, 3]));
+
+        let mut model = lits(&[1, -2, 3]);
+        stack.extend(&mut model);
+        assert_eq!(model, lits(&[1, 2, 3]));
+
+        let mut model = lits(&[-1, 2, -3]);
+        stack.extend(&mut model);
+        assert_eq!(model, lits(&[-1, -2, -3]));
+    }
+
+    #[test]
+    fn test_postsolve_partial_model() {
+        let mut stack = ReconstructionStack::new(3);
+        stack.push(Literal::from_dimacs(2), &lits(&[2, -1]));
+        stack.push(Literal::from_dimacs(-2), &lits(&[-2, 3]));
+
+        // variable 3 is missing and falsified, variable 4 was added by the simplification
+        assert_eq!(stack.postsolve(&lits(&[-1, 4])), lits(&[-1, -2, -3]));
+        assert_eq!(stack.postsolve(&lits(&[1, 3])), lits(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn test_restore() {
+        let mut stack = ReconstructionStack::new(3);
+        stack.push(Literal::from_dimacs(2), &lits(&[2, -1]));
+        stack.push(Literal::from_dimacs(3), &lits(&[3, 1]));
+        stack.push(Literal::from_dimacs(-2), &lits(&[-2, 3]));
+
+        let variable = Literal::from_dimacs(2).variable();
+        assert!(stack.is_eliminated(variable));
+        assert_eq!(
+            stack.restore(variable),
+            vec![lits(&[2, -1]), lits(&[-2, 3])]
+        );
+        assert!(!stack.is_eliminated(variable));
+        assert!(stack.restore(variable).is_empty());
+        assert_eq!(stack.to_string(), "p stack 3\n3 3 1 0\n");
+    }
+
+    #[test]
+    fn test_renumbered() {
+        let mut stack = ReconstructionStack::new(2);
+        stack.push(Literal::from_dimacs(-2), &lits(&[-2, 3]));
+
+        let variables = [1, 3, 2].map(Variable::from_dimacs);
+        let renumbered = stack.renumbered(&variables, 3);
+        assert_eq!(renumbered.to_string(), "p stack 3\n-3 -3 2 0\n");
+        assert!(renumbered.is_eliminated(Variable::from_dimacs(3)));
+    }
+
+    #[test]
+    fn test_parse_model() {
+        assert_eq!(
+            parse_model("s SATISFIABLE\nv 1 -2\nv 3 0\n"),
+            Ok(lits(&[1, -2, 3]))
+        );
+        assert_eq!(
+            parse_model("s SATISFIABLE\nv [1, -2, 3]\n"),
+            Ok(lits(&[1, -2, 3]))
+        );
+        assert_eq!(parse_model("SAT\n1 -2 3 0\n"), Ok(lits(&[1, -2, 3])));
+        assert_eq!(
+            parse_model("SAT\n1 two 0\n"),
+            Err(ParseError::new(2, "invalid literal two"))
+        );
+    }
+
+    #[test]
+    fn test_text_roundtrip() {
+        let mut stack = ReconstructionStack::new(3);
+        stack.push(Literal::from_dimacs(2), &lits(&[2, -1]));
+        stack.push(Literal::from_dimacs(-2), &lits(&[-2, 3]));
+
+        let text = stack.to_string();
+        assert_eq!(text, "p stack 3\n2 2 -1 0\n-2 -2 3 0\n");
+        assert_eq!(ReconstructionStack::from_text(&text), Ok(stack));
+    }
+
+    #[test]
+    fn test_malformed_stack() {
+        let error = |text| ReconstructionStack::from_text(text).unwrap_err();
+        assert_eq!(
+            error("p stack x\n"),
+            ParseError::new(1, "invalid variable count")
+        );
+        assert_eq!(
+            error("p stack 2\n\n0\n"),
+            ParseError::new(3, "witness literal not in the clause")
+        );
+        assert_eq!(
+            error("p stack 2\n2 2 -1 0\n2 -1 0\n"),
+            ParseError::new(3, "witness literal not in the clause")
+        );
+        assert_eq!(
+            error("p stack 2\n2 2 -1\n"),
+            ParseError::new(2, "entry not terminated by 0")
+        );
+        assert_eq!(
+            error("p stack 2\n2 2 a 0\n"),
+            ParseError::new(2, "invalid literal a")
+        );
+    }
+}
@@ -1,25 +1,82 @@
-use crate::analyze::{analyze, ConflictAnalysis};
+use crate::analyze::{analyze, reward_unassigned, ConflictAnalysis};
 use crate::bcp::{propagate, trail, BcpContext};
-use crate::literal::Literal;
+use crate::literal::{Literal, Variable};
+use crate::reconstruct::ReconstructionStack;
 use crate::resize::Resize;
 use crate::search::dlis::Dlis;
+use crate::search::hints::DecisionHints;
+use crate::search::mode::{DecisionQueue, Mode, Modes};
+use crate::search::policy::{ClauseReductionPolicy, ConflictInfo};
+use crate::search::reduce::{reduce_learned_clauses, ReduceSchedule};
+use crate::search::rephase::RephaseSchedule;
+use crate::search::schedule::InprocessingSchedule;
+use crate::simplify::inprocess;
+use crate::simplify::top_level::simplify_top_level;
+use crate::statistics::Statistics;
 
 mod dlis;
 mod first_unassigned;
+mod heap;
+pub mod heuristic;
+mod hints;
+mod learning_rate;
+pub mod mode;
+pub mod policy;
+mod reduce;
+mod rephase;
+pub mod restart;
+mod schedule;
+mod vmtf;
+mod vsids;
 
 /// outer data structures for CDCL search
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct SearchContext {
     pub bcp: BcpContext,
     pub conflict_analysis: ConflictAnalysis,
     pub dlis: Dlis,
-    pub use_dlis: bool,
+    /// policies, decision queues and restart schedules of the focused and stable modes
+    pub modes: Modes,
+    pub hints: DecisionHints,
+    /// number of top level assignments during the last top level simplification
+    pub simplified_assignments: usize,
+    pub stats: Statistics,
+    /// clauses removed by variable elimination
+    pub reconstruction: ReconstructionStack,
+    /// reference counts of frozen variables, which must not be eliminated
+    pub frozen: Vec<u32>,
+    pub inprocessing: InprocessingSchedule,
+    pub rephasing: RephaseSchedule,
+    pub reduction: Box<dyn ClauseReductionPolicy>,
+}
+
+impl Default for SearchContext {
+    fn default() -> Self {
+        SearchContext {
+            bcp: Default::default(),
+            conflict_analysis: Default::default(),
+            dlis: Default::default(),
+            modes: Default::default(),
+            hints: Default::default(),
+            simplified_assignments: 0,
+            stats: Default::default(),
+            reconstruction: Default::default(),
+            frozen: vec![],
+            inprocessing: Default::default(),
+            rephasing: Default::default(),
+            reduction: Box::new(ReduceSchedule::default()),
+        }
+    }
 }
 
 impl Resize for SearchContext {
     fn resize(&mut self, var_count: usize) {
         self.bcp.resize(var_count);
+        self.conflict_analysis.resize(var_count);
         self.dlis.resize(var_count);
+        self.modes.resize(var_count);
+        self.hints.resize(var_count);
+        self.frozen.resize(var_count, 0);
     }
 }
 
@@ -35,13 +92,76 @@ pub fn search(ctx: &mut SearchContext) -> Option<bool> {
         Err(conflict) => {
             // conflict without assumptions -> UNSAT
             if ctx.bcp.trail.current_decision_level() == trail::TOP_DECISION_LEVEL {
-                ctx.bcp.is_unsat = true;
+                ctx.bcp.set_unsat();
                 return Some(false);
             }
             // or we learn an asserting clause, and backtrack
+            let trail_length = ctx.bcp.trail.steps().len();
+            ctx.conflict_analysis.rewards = ctx.modes.current().queue.rewards();
             analyze(conflict, &mut ctx.conflict_analysis, &mut ctx.bcp);
+            ctx.stats.conflicts += 1;
+            let strengthened_reasons = ctx.conflict_analysis.strengthened_reasons();
+            ctx.stats.strengthened_reasons += strengthened_reasons.len() as u64;
+            for (index, literals) in strengthened_reasons {
+                ctx.dlis.strengthen_clause(*index, literals);
+            }
+            if let [a, b] = *ctx.conflict_analysis.derived_clause() {
+                ctx.dlis.add_binary_clause([a, b]);
+            }
+            match ctx.modes.mode {
+                Mode::Focused => ctx.stats.focused_conflicts += 1,
+                Mode::Stable => ctx.stats.stable_conflicts += 1,
+            }
+
+            let conflict = ConflictInfo {
+                glue: ctx.conflict_analysis.glue(),
+                trail_length,
+                conflicts: ctx.stats.conflicts,
+            };
+            ctx.reduction.conflict(&conflict);
+            let state = ctx.modes.current_mut();
+            state.restarts.conflict(&conflict);
+            state
+                .queue
+                .reward(ctx.conflict_analysis.rewarded_variables());
+            state.queue.bump(
+                ctx.conflict_analysis.bumped_variables_mut(),
+                &ctx.bcp.assignment,
+            );
         }
         Ok(_) => {
+            if ctx.modes.switching.is_due(ctx.bcp.ticks) {
+                switch_mode(ctx);
+                return None;
+            }
+            if ctx.inprocessing.is_due(ctx.stats.conflicts) {
+                run_inprocessing(ctx);
+                return None;
+            }
+            if ctx.rephasing.is_due(ctx.stats.conflicts) {
+                rephase(ctx);
+                return None;
+            }
+            if ctx.reduction.should_reduce() {
+                reduce(ctx);
+                return None;
+            }
+            if ctx.modes.current_mut().restarts.should_restart() {
+                partial_restart(ctx);
+                ctx.modes.current_mut().restarts.restarted();
+                ctx.stats.restarts += 1;
+                return None;
+            }
+
+            // new units were learned, remove satisfied clauses and false literals
+            if ctx.bcp.trail.current_decision_level() == trail::TOP_DECISION_LEVEL
+                && ctx.bcp.trail.steps().len() > ctx.simplified_assignments
+            {
+                simplify_top_level(&mut ctx.bcp);
+                ctx.dlis.invalidate();
+                ctx.simplified_assignments = ctx.bcp.trail.steps().len();
+            }
+
             if let Some(literal) = make_decision(ctx) {
                 // no conflict but not all variables are assigned -> solver decision
                 trail::decide_and_assign(&mut ctx.bcp, literal);
@@ -55,14 +175,312 @@ pub fn search(ctx: &mut SearchContext) -> Option<bool> {
     None
 }
 
+/// Backtracks to the top decision level, target phases are collected anew afterwards
+fn restart(ctx: &mut SearchContext) {
+    if ctx.bcp.trail.current_decision_level() != trail::TOP_DECISION_LEVEL {
+        backtrack_for_restart(ctx, trail::TOP_DECISION_LEVEL);
+    }
+    ctx.bcp.phases.reset_target();
+}
+
+/// Restarts the search, but keeps the decision levels that would be decided again in the same
+/// order (van der Tak et al. 2011)
+fn partial_restart(ctx: &mut SearchContext) {
+    let level = reuse_trail_level(ctx);
+    if level != trail::TOP_DECISION_LEVEL {
+        ctx.stats.reused_trails += 1;
+        ctx.stats.reused_levels += level as u64;
+    }
+
+    if level < ctx.bcp.trail.current_decision_level() {
+        backtrack_for_restart(ctx, level);
+    }
+    ctx.bcp.phases.reset_target();
+}
+
+/// Backtracks to a lower decision level, LRB rewards the unassigned variables as after conflicts
+fn backtrack_for_restart(ctx: &mut SearchContext, decision_level: u32) {
+    let queue = &mut ctx.modes.current_mut().queue;
+    ctx.conflict_analysis.rewards = queue.rewards();
+    reward_unassigned(&mut ctx.conflict_analysis, &ctx.bcp.trail, decision_level);
+    queue.reward(ctx.conflict_analysis.rewarded_variables());
+
+    trail::backtrack(&mut ctx.bcp, decision_level);
+}
+
+/// Returns the highest decision level such that the decisions up to it all have a higher
+/// priority than the next decision of the current queue, decisions of the decision order are kept
+/// as long as the order is
+fn reuse_trail_level(ctx: &mut SearchContext) -> u32 {
+    let mut level = trail::TOP_DECISION_LEVEL;
+    while level < ctx.bcp.trail.current_decision_level()
+        && ctx
+            .hints
+            .is_ordered(ctx.bcp.trail.decision(level + 1).variable())
+    {
+        level += 1;
+    }
+
+    follow_unassigned(ctx);
+    if ctx.hints.decide(&ctx.bcp.assignment).is_some() {
+        return level;
+    }
+
+    let queue = &mut ctx.modes.current_mut().queue;
+    let Some(next) = queue.next_decision(&ctx.bcp.assignment) else {
+        return level;
+    };
+
+    while level < ctx.bcp.trail.current_decision_level()
+        && queue.prefers(ctx.bcp.trail.decision(level + 1).variable(), next)
+    {
+        level += 1;
+    }
+    level
+}
+
+/// Restarts the search in the other mode, whose decision queue did not follow the trail
+fn switch_mode(ctx: &mut SearchContext) {
+    restart(ctx);
+
+    ctx.modes.mode = match ctx.modes.mode {
+        Mode::Focused => Mode::Stable,
+        Mode::Stable => Mode::Focused,
+    };
+    ctx.modes.switching.switched(ctx.bcp.ticks);
+    ctx.modes
+        .current_mut()
+        .queue
+        .reset_search(&ctx.bcp.assignment);
+    ctx.dlis.reset_search();
+
+    ctx.stats.mode_switches += 1;
+}
+
+/// Restarts the search and simplifies the formula on the top decision level
+fn run_inprocessing(ctx: &mut SearchContext) {
+    restart(ctx);
+
+    let effort = ctx.inprocessing.effort(ctx.bcp.ticks);
+    ctx.stats.inprocessing_rounds += 1;
+    ctx.dlis.invalidate();
+
+    inprocess(
+        &mut ctx.bcp,
+        &mut ctx.reconstruction,
+        &ctx.frozen,
+        effort,
+        &mut ctx.stats,
+    );
+
+    ctx.simplified_assignments = ctx.bcp.trail.steps().len();
+    ctx.inprocessing
+        .finish_round(ctx.stats.conflicts, ctx.bcp.ticks);
+}
+
+/// Restarts the search and resets the saved phases
+fn rephase(ctx: &mut SearchContext) {
+    restart(ctx);
+
+    ctx.rephasing.rephase(&mut ctx.bcp, ctx.stats.conflicts);
+    ctx.stats.rephases += 1;
+}
+
+/// Deletes learned clauses that are not useful anymore, without restarting
+fn reduce(ctx: &mut SearchContext) {
+    let deleted = reduce_learned_clauses(&mut ctx.bcp);
+    ctx.dlis.delete_clauses(&deleted, &ctx.bcp.long_clauses);
+    ctx.reduction.reduced(deleted.len());
+
+    ctx.stats.reductions += 1;
+    ctx.stats.reduced_clauses += deleted.len() as u64;
+}
+
+/// Chooses a variable from the decision order or with the decision heuristic of the current mode
+/// and assigns it its forced, saved or target phase. Heuristics that choose literals instead of
+/// variables keep their phase unless it is forced.
 fn make_decision(ctx: &mut SearchContext) -> Option<Literal> {
-    if ctx.use_dlis {
-        ctx.dlis.decide(
-            &ctx.bcp.assignment,
-            &ctx.bcp.long_clauses,
-            &ctx.bcp.binary_clauses,
-        )
-    } else {
-        first_unassigned::first_unassigned(&ctx.bcp.assignment)
+    update_priorities(ctx);
+    follow_unassigned(ctx);
+
+    let choice = match ctx.hints.decide(&ctx.bcp.assignment) {
+        Some(variable) => Choice::Variable(variable),
+        None => choose(ctx)?,
+    };
+
+    match choice {
+        Choice::Variable(variable) => {
+            let target = ctx.modes.current().policy.target_phases;
+            Some(ctx.bcp.phases.decision(variable, target))
+        }
+        Choice::Literal(literal) => Some(ctx.bcp.phases.force(literal)),
+    }
+}
+
+/// Makes the variables unassigned since the last decision available to the decision order and the
+/// queue of the current mode
+fn follow_unassigned(ctx: &mut SearchContext) {
+    let queue = &mut ctx.modes.current_mut().queue;
+    for variable in ctx.bcp.trail.take_unassigned() {
+        ctx.hints.unassigned(variable);
+        queue.unassigned(variable);
+    }
+}
+
+/// Raises the scores of the decision heuristics of both modes by the priorities changed by the
+/// user
+fn update_priorities(ctx: &mut SearchContext) {
+    let mut changes = ctx.hints.take_changed_priorities();
+    // VMTF decides the variable that was raised last, which has to be the highest priority
+    changes.sort_by(|a, b| a.2.total_cmp(&b.2));
+    for (variable, previous, priority) in changes {
+        ctx.dlis.prioritize(variable, priority);
+        for state in [&mut ctx.modes.focused, &mut ctx.modes.stable] {
+            state.queue.prioritize(variable, previous, priority);
+        }
+    }
+}
+
+/// Raises the scores of a new decision queue of the mode by the priorities the other heuristics
+/// got already
+pub fn prioritize_queue(ctx: &mut SearchContext, mode: Mode) {
+    let state = match mode {
+        Mode::Focused => &mut ctx.modes.focused,
+        Mode::Stable => &mut ctx.modes.stable,
+    };
+    let mut priorities = ctx.hints.applied_priorities();
+    priorities.sort_by(|a, b| a.1.total_cmp(&b.1));
+    for (variable, priority) in priorities {
+        state.queue.prioritize(variable, 0.0, priority);
+    }
+}
+
+/// The decision of a heuristic, with or without its phase
+enum Choice {
+    Variable(Variable),
+    Literal(Literal),
+}
+
+fn choose(ctx: &mut SearchContext) -> Option<Choice> {
+    let assignment = &ctx.bcp.assignment;
+    let queue = &mut ctx.modes.current_mut().queue;
+
+    let choice = match queue {
+        DecisionQueue::Vmtf(vmtf) => Choice::Variable(vmtf.decide(assignment)?),
+        DecisionQueue::Vsids(vsids) => Choice::Variable(vsids.decide(assignment)?),
+        DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) => {
+            Choice::Variable(queue.decide(assignment)?)
+        }
+        DecisionQueue::Scoring(scoring) => {
+            Choice::Literal(ctx.dlis.decide(*scoring, &mut ctx.bcp)?)
+        }
+        DecisionQueue::FirstUnassigned => {
+            Choice::Literal(first_unassigned::first_unassigned(assignment)?)
+        }
+        DecisionQueue::Custom(custom) => {
+            Choice::Literal(custom.decide(assignment, &mut ctx.bcp.trail)?)
+        }
+    };
+    Some(choice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+    use crate::search::mode::{Decisions, ModePolicy};
+
+    #[test]
+    fn test_partial_restart_reuses_trail() {
+        let cnf = CNF::from_dimacs("1 2 3 4 5 0\n");
+        let mut ctx = SearchContext::default();
+        ctx.resize(cnf.variable_count());
+        for clause in cnf.clauses() {
+            ctx.bcp.add_clause(clause.literals());
+        }
+        let policy = ModePolicy {
+            decisions: Decisions::Vsids,
+            ..ModePolicy::focused()
+        };
+        ctx.modes
+            .set_policy(Mode::Focused, policy, cnf.variable_count());
+
+        // scores a > b > c
+        let [a, b, c, d] = [3, 1, 4, 2].map(Variable::from_dimacs);
+        let queue = &mut ctx.modes.current_mut().queue;
+        for mut bumped in [vec![c], vec![a, b], vec![a, b], vec![a]] {
+            queue.bump(&mut bumped, &ctx.bcp.assignment);
+        }
+        for _ in 0..3 {
+            let literal = make_decision(&mut ctx).unwrap();
+            trail::decide_and_assign(&mut ctx.bcp, literal);
+            propagate(&mut ctx.bcp).unwrap();
+        }
+        assert_eq!(ctx.bcp.trail.decision(3).variable(), c);
+
+        // the unassigned d is now preferred over c, but not over a and b
+        let queue = &mut ctx.modes.current_mut().queue;
+        queue.bump(&mut [d], &ctx.bcp.assignment);
+        assert!(queue.prefers(b, d) && queue.prefers(d, c));
+
+        partial_restart(&mut ctx);
+        assert_eq!(ctx.bcp.trail.current_decision_level(), 2);
+        assert_eq!(ctx.bcp.trail.decision(1).variable(), a);
+        assert_eq!(ctx.bcp.trail.decision(2).variable(), b);
+        assert_eq!(ctx.stats.reused_trails, 1);
+        assert_eq!(ctx.stats.reused_levels, 2);
+
+        // nothing is reused if the next decision is preferred over all decisions
+        let queue = &mut ctx.modes.current_mut().queue;
+        while !queue.prefers(c, a) {
+            queue.bump(&mut [c], &ctx.bcp.assignment);
+        }
+        partial_restart(&mut ctx);
+        assert_eq!(ctx.bcp.trail.current_decision_level(), 0);
+        assert_eq!(ctx.stats.reused_trails, 1);
+        assert_eq!(ctx.stats.reused_levels, 2);
+    }
+
+    #[test]
+    fn test_priorities_raise_scores() {
+        // all variables have the same scores before the priorities
+        let cnf = CNF::from_dimacs("1 2 0\n3 4 0\n");
+        for decisions in [
+            Decisions::Vmtf,
+            Decisions::Vsids,
+            Decisions::Chb,
+            Decisions::Lrb,
+            Decisions::Dlis,
+            Decisions::JeroslowWang,
+            Decisions::TwoSidedJeroslowWang,
+            Decisions::Moms,
+            Decisions::Bohm,
+        ] {
+            let mut ctx = SearchContext::default();
+            ctx.resize(cnf.variable_count());
+            for clause in cnf.clauses() {
+                ctx.bcp.add_clause(clause.literals());
+            }
+            let policy = ModePolicy {
+                decisions,
+                ..ModePolicy::focused()
+            };
+            ctx.modes
+                .set_policy(Mode::Focused, policy, cnf.variable_count());
+
+            let [a, b, c] = [3, 2, 4].map(Variable::from_dimacs);
+            ctx.hints.set_priority(c, 1.0);
+            ctx.hints.set_priority(a, 2.0);
+            ctx.hints.set_priority(b, 1.0);
+            let decision = make_decision(&mut ctx).unwrap();
+            assert_eq!(decision.variable(), a, "{decisions:?}");
+
+            // a new queue gets the priorities as well
+            ctx.modes
+                .set_policy(Mode::Focused, policy, cnf.variable_count());
+            prioritize_queue(&mut ctx, Mode::Focused);
+            let decision = make_decision(&mut ctx).unwrap();
+            assert_eq!(decision.variable(), a, "{decisions:?}");
+        }
     }
 }
@@ -1,11 +1,68 @@
-use crate::assignment::VariableAssignment;
-use crate::literal::Literal;
+//! Decision heuristics that count the occurrences of literals in the unresolved clauses.
+//!
+//! The counts are not updated in `trail::assign` and `trail::backtrack`. Propagation assigns
+//! literals while it holds the watchlists, and the counts are only read by decisions. So before
+//! each decision the counts replay the steps assigned and unassigned since the last one, like the
+//! other decision heuristics do. Backtracking is found with the backtracked length of the trail,
+//! which only the heuristic of the current mode takes. After a mode switch the counted trail is
+//! compared with the trail instead.
+//!
+//! The watches cannot tell when a clause becomes satisfied. A long clause is watched by only two
+//! of its literals, but any of its literals satisfies it. So the long clauses are found by their
+//! index in occurrence lists, and only the binary clauses are visited in the watchlists.
+
+use crate::literal::{Literal, Variable};
 
-use crate::bcp::binary_clauses::BinaryClauses;
 use crate::bcp::long_clauses::LongClauses;
+use crate::bcp::BcpContext;
+use crate::clause::ClauseIndex;
 use crate::resize::Resize;
+use crate::search::heap::VariableHeap;
+
+use std::cmp::Ordering;
+
+/// Clauses with more unassigned literals are counted as clauses of this size
+const MAX_COUNTED_SIZE: usize = 16;
+
+/// Weight of the two-sided count in the MOMS score, relative to the product of both polarities
+const MOMS_WEIGHT: f64 = 1024.0;
+
+/// Weights of the more and the less frequent polarity in the scores of Böhm's heuristic
+const BOHM_ALPHA: u32 = 1;
+const BOHM_BETA: u32 = 2;
+
+/// Marks long clauses that are not counted by DLIS
+const NOT_COUNTED: u32 = u32::MAX;
+
+/// Returns the weight `2^-size` of a clause in the Jeroslow-Wang sums
+fn size_weight(size: usize) -> f64 {
+    0.5f64.powi(size as i32)
+}
 
-/// Implements the Dynamic Largest Individual Sum (DLIS) decision heuristic.
+/// Decision heuristics that score the literals by their occurrences in the unresolved clauses.
+/// The counts are maintained while the trail changes, the counts by clause size that the other
+/// heuristics use only after the first of their decisions.
+/// Priorities set by the user are added to the scores of the variables, Böhm's heuristic compares
+/// them only for equal counts.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Scoring {
+    /// the literal with the most unresolved clauses
+    Dlis,
+    /// the literal with the highest sum of `2^-k` over its unresolved clauses with `k` unassigned
+    /// literals (Jeroslow and Wang 1990)
+    JeroslowWang,
+    /// the variable with the highest Jeroslow-Wang sum of both literals
+    TwoSidedJeroslowWang,
+    /// the variable with the maximum occurrences in the clauses of minimum size, which prefers
+    /// variables that occur in both polarities (Freeman 1995)
+    Moms,
+    /// the variable with the lexicographically largest vector of occurrences by clause size,
+    /// weighting the less frequent polarity higher (Buro and Kleine Büning 1992)
+    Bohm,
+}
+
+/// Implements the Dynamic Largest Individual Sum (DLIS) decision heuristic and the other
+/// [`Scoring`] heuristics.
 ///
 /// Approach: choose literal that satisfies most unresolved clauses
 ///  - for each variable x, calculate
@@ -13,86 +70,699 @@ use crate::resize::Resize;
 ///     - C(-x): number of unresolved clauses with -x
 ///  - select two variables x and y that maximize these two metrics from all the variables
 ///  - if C(x) > C(-y) set x to true, else set y to false
+///
+/// The other heuristics count the unresolved clauses of each literal by the number of unassigned
+/// literals in the clause and weight short clauses higher.
 #[derive(Default, Debug)]
 pub struct Dlis {
-    // maps a literal to its number of unresolved clauses
-    lit_scores: Vec<u32>,
+    counters: UnresolvedCounters,
 }
 
 impl Resize for Dlis {
     fn resize(&mut self, variable_count: usize) {
-        self.lit_scores.resize(variable_count * 2, 0);
+        self.counters.resize(variable_count);
     }
 }
 
 impl Dlis {
-    /// Returns the next decision literal according to DLIS or `None` if no variables are unassigned.
-    pub fn decide(
-        &mut self,
-        assignment: &VariableAssignment,
-        long: &LongClauses,
-        binary: &BinaryClauses,
-    ) -> Option<Literal> {
-        let unassigned_literals = assignment
-            .unassigned()
+    /// Returns the next decision literal according to the scoring heuristic or `None` if no
+    /// unassigned variable occurs in an unresolved clause.
+    pub fn decide(&mut self, scoring: Scoring, bcp: &mut BcpContext) -> Option<Literal> {
+        if scoring == Scoring::Dlis {
+            return self.counters.decide(bcp);
+        }
+
+        self.counters.follow_trail_by_size(bcp);
+
+        let unassigned = bcp.assignment.unassigned();
+        let min_size = self.min_size();
+        let mut best: Option<([Literal; 2], f64)> = None;
+        for variable in unassigned {
+            let literals = [true, false].map(|s| Literal::from_variable(&variable, s));
+            // the maintained count of an unassigned literal is its total count by size
+            if literals
+                .iter()
+                .all(|l| self.counters.unresolved[l.as_index()] == 0)
+            {
+                continue;
+            }
+            let score = self.variable_score(scoring, literals, min_size);
+            let is_better = match best {
+                None => true,
+                Some((best_literals, _)) if scoring == Scoring::Bohm => {
+                    self.compare_bohm(literals, best_literals) == Ordering::Greater
+                }
+                Some((_, best_score)) => score > best_score,
+            };
+            if is_better {
+                best = Some((literals, score));
+            }
+        }
+
+        best.map(|([positive, negative], _)| {
+            if self.compare_literals(scoring, negative, positive, min_size) == Ordering::Greater {
+                negative
+            } else {
+                positive
+            }
+        })
+    }
+
+    /// Counts a binary clause learned during the search, learned long clauses are counted on the
+    /// next decision
+    pub fn add_binary_clause(&mut self, literals: [Literal; 2]) {
+        if self.counters.valid {
+            self.counters.add_binary_clause(literals, 1);
+            self.counters.change_binary_size_counts(literals, 1);
+        }
+    }
+
+    /// Counts a reason strengthened during the search without its implied first literal, given
+    /// the literals it had before
+    pub fn strengthen_clause(&mut self, index: ClauseIndex, literals: &[Literal]) {
+        if self.counters.valid {
+            self.counters.strengthen_clause(index, literals);
+        }
+    }
+
+    /// Stops counting the long clauses deleted by a reduction, whose literals are still stored
+    pub fn delete_clauses(&mut self, indices: &[ClauseIndex], long_clauses: &LongClauses) {
+        if self.counters.valid {
+            // clauses learned since the last decision were not counted yet
+            let counted = self.counters.true_literals.len();
+            for &index in indices.iter().filter(|&&i| i < counted) {
+                self.counters
+                    .remove_long_clause(index, long_clauses.literals(index));
+            }
+            self.counters.compact_occurrences();
+        }
+    }
+
+    /// Sets the priority set by the user, which is added to the score of the variable if it occurs
+    /// in an unresolved clause
+    pub fn prioritize(&mut self, variable: Variable, priority: f64) {
+        self.counters.priorities[variable.as_index()] = priority;
+        self.counters.update_score(variable);
+    }
+
+    /// The trail changes without the counters following them, e.g. while the other mode is
+    /// searching
+    pub fn reset_search(&mut self) {
+        self.counters.stale = true;
+    }
+
+    /// Counts all clauses anew for the next decision, e.g. after clauses were added or simplified
+    /// or the long clauses were moved by a garbage collection
+    pub fn invalidate(&mut self) {
+        self.counters.valid = false;
+    }
+
+    fn count(&self, size: usize, literal: Literal) -> u32 {
+        self.counters.size_counts[literal.as_index()][size]
+    }
+
+    /// Returns the number of unresolved clauses with the literal
+    fn total_count(&self, literal: Literal) -> u32 {
+        self.counters.size_counts[literal.as_index()].iter().sum()
+    }
+
+    fn jeroslow_wang(&self, literal: Literal) -> f64 {
+        self.counters.jeroslow_wang[literal.as_index()]
+    }
+
+    /// Returns the smallest size of the unresolved clauses
+    fn min_size(&self) -> usize {
+        self.counters
+            .size_counts
             .iter()
-            .flat_map(|v| [true, false].iter().map(|s| Literal::from_variable(v, *s)))
-            .collect::<Vec<_>>();
+            .filter_map(|counts| counts.iter().position(|c| *c > 0))
+            .min()
+            .unwrap_or(0)
+    }
 
-        if unassigned_literals.is_empty() {
-            return None;
+    /// Returns the score of a variable, given positive literal first, which is compared by all
+    /// heuristics except Böhm's
+    fn variable_score(&self, scoring: Scoring, [p, n]: [Literal; 2], min_size: usize) -> f64 {
+        let priority = self.priority(p);
+        match scoring {
+            Scoring::Dlis => self.total_count(p).max(self.total_count(n)) as f64 + priority,
+            Scoring::JeroslowWang => self.jeroslow_wang(p).max(self.jeroslow_wang(n)) + priority,
+            Scoring::TwoSidedJeroslowWang => {
+                self.jeroslow_wang(p) + self.jeroslow_wang(n) + priority
+            }
+            Scoring::Moms => {
+                let (p, n) = (
+                    self.count(min_size, p) as f64,
+                    self.count(min_size, n) as f64,
+                );
+                (p + n + priority) * MOMS_WEIGHT + p * n
+            }
+            // compared lexicographically by `compare_bohm`
+            Scoring::Bohm => 0.0,
         }
+    }
 
-        self.calculate_literal_scores(unassigned_literals, assignment, long, binary);
+    /// Compares the literals of two variables by Böhm's heuristic, given positive literal first
+    fn compare_bohm(&self, a: [Literal; 2], b: [Literal; 2]) -> Ordering {
+        let score = |[p, n]: [Literal; 2], size: usize| {
+            let (p, n) = (self.count(size, p), self.count(size, n));
+            BOHM_ALPHA * p.max(n) + BOHM_BETA * p.min(n)
+        };
+        (0..=MAX_COUNTED_SIZE)
+            .map(|size| score(a, size).cmp(&score(b, size)))
+            .find(|o| o.is_ne())
+            .unwrap_or_else(|| self.priority(a[0]).total_cmp(&self.priority(b[0])))
+    }
+
+    fn priority(&self, literal: Literal) -> f64 {
+        self.counters.priorities[literal.variable().as_index()]
+    }
 
-        let mut max_score: u32 = 0;
-        let mut max_lit_code = 0;
-        for (lit_code, score) in self.lit_scores.iter().enumerate() {
-            if *score > max_score {
-                max_score = *score;
-                max_lit_code = lit_code;
+    /// Compares the scores of two literals, which decides the polarity of the chosen variable
+    fn compare_literals(
+        &self,
+        scoring: Scoring,
+        a: Literal,
+        b: Literal,
+        min_size: usize,
+    ) -> Ordering {
+        match scoring {
+            Scoring::Dlis => self.total_count(a).cmp(&self.total_count(b)),
+            Scoring::JeroslowWang | Scoring::TwoSidedJeroslowWang => {
+                self.jeroslow_wang(a).total_cmp(&self.jeroslow_wang(b))
             }
+            Scoring::Moms => self.count(min_size, a).cmp(&self.count(min_size, b)),
+            Scoring::Bohm => (0..=MAX_COUNTED_SIZE)
+                .map(|size| self.count(size, a).cmp(&self.count(size, b)))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal),
         }
+    }
+}
 
-        if max_score > 0 {
-            Some(Literal::from_code(max_lit_code))
-        } else {
+/// Number of unresolved clauses of every literal for DLIS, and by the number of unassigned
+/// literals in the clause for the other heuristics, which are updated with the steps added to and
+/// removed from the trail since the last decision instead of counting anew.
+///
+/// Learned, strengthened and deleted clauses are updated, the counters are rebuilt after other
+/// changes of the formula, whose garbage collection moves the long clauses.
+#[derive(Default, Debug)]
+struct UnresolvedCounters {
+    /// false if the counters have to be rebuilt before the next decision
+    valid: bool,
+    /// the trail changed while the counters were not following it
+    stale: bool,
+    /// maps the index of a long clause to its number of counted true literals, `NOT_COUNTED` for
+    /// deleted clauses, the clauses after the end are counted on the next decision
+    true_literals: Vec<u32>,
+    /// maps the index of a long clause to its number of counted false literals, only maintained
+    /// with the counts by size
+    false_literals: Vec<u32>,
+    /// maps a literal to the indices of the long clauses with it, including clauses that are not
+    /// counted anymore
+    occurrences: Vec<Vec<u32>>,
+    /// number of entries in `occurrences` and the number of them that are not counted anymore
+    occurrence_count: usize,
+    removed_occurrences: usize,
+    /// maps a literal to the number of counted clauses with it and without true literals
+    unresolved: Vec<u32>,
+    /// true once a heuristic used the counts by clause size, which are maintained from then on
+    count_sizes: bool,
+    /// maps an unassigned literal and a clause size to the number of unresolved clauses with it
+    /// and this number of unassigned literals
+    size_counts: Vec<[u32; MAX_COUNTED_SIZE + 1]>,
+    /// maps an unassigned literal to the sum of `2^-size` over its counts by size, which is exact
+    /// because the weights are powers of two
+    jeroslow_wang: Vec<f64>,
+    /// maps a variable to the higher count of its literals plus its priority, the order of the
+    /// heap, lower variables are preferred on equal scores
+    scores: Vec<f64>,
+    /// maps a variable to the priority set by the user
+    priorities: Vec<f64>,
+    /// all variables that are unassigned, and assigned ones that were not popped yet
+    heap: VariableHeap,
+    /// the prefix of the trail whose assignments are included in the counts
+    counted_trail: Vec<Literal>,
+    /// maps a literal to true if it is on `counted_trail`
+    counted_true: Vec<bool>,
+}
+
+impl Resize for UnresolvedCounters {
+    fn resize(&mut self, variable_count: usize) {
+        self.valid = false;
+        self.occurrences.resize(variable_count * 2, vec![]);
+        self.unresolved.resize(variable_count * 2, 0);
+        self.size_counts
+            .resize(variable_count * 2, [0; MAX_COUNTED_SIZE + 1]);
+        self.jeroslow_wang.resize(variable_count * 2, 0.0);
+        self.counted_true.resize(variable_count * 2, false);
+        self.scores.resize(variable_count, 0.0);
+        self.priorities.resize(variable_count, 0.0);
+        self.heap.resize(variable_count);
+    }
+}
+
+impl UnresolvedCounters {
+    fn decide(&mut self, bcp: &mut BcpContext) -> Option<Literal> {
+        self.follow_trail(bcp);
+
+        let variable = loop {
+            let variable = self.heap.peek()?;
+            if bcp.assignment.is_unknown(variable) {
+                break variable;
+            }
+            self.heap.pop(&self.scores);
+        };
+
+        let positive = Literal::from_variable(&variable, true);
+        let [positive_count, negative_count] =
+            [positive, !positive].map(|l| self.unresolved[l.as_index()]);
+        if positive_count == 0 && negative_count == 0 {
             None
+        } else if positive_count >= negative_count {
+            Some(positive)
+        } else {
+            Some(!positive)
+        }
+    }
+
+    /// Updates the counts and the counts by clause size to the current trail, the counts by size
+    /// are counted anew when they are used for the first time
+    fn follow_trail_by_size(&mut self, bcp: &mut BcpContext) {
+        if !std::mem::replace(&mut self.count_sizes, true) {
+            self.valid = false;
+        }
+        self.follow_trail(bcp);
+    }
+
+    /// Updates the counts to the current trail, or rebuilds them if they were invalidated
+    fn follow_trail(&mut self, bcp: &mut BcpContext) {
+        if !self.valid {
+            self.rebuild(bcp);
+            return;
+        }
+
+        let backtracked_length = bcp.trail.take_backtracked_length();
+        let kept = if std::mem::take(&mut self.stale) {
+            self.counted_trail
+                .iter()
+                .zip(bcp.trail.steps())
+                .take_while(|(&literal, step)| literal == step.assigned_literal)
+                .count()
+        } else {
+            backtracked_length.min(self.counted_trail.len())
+        };
+        while self.counted_trail.len() > kept {
+            self.unassign(bcp);
+        }
+        self.add_learned_clauses(&bcp.long_clauses);
+        for step in &bcp.trail.steps()[kept..] {
+            self.assign(step.assigned_literal, bcp);
+        }
+    }
+
+    fn rebuild(&mut self, bcp: &mut BcpContext) {
+        self.true_literals.clear();
+        self.false_literals.clear();
+        self.occurrences.iter_mut().for_each(|o| o.clear());
+        self.occurrence_count = 0;
+        self.removed_occurrences = 0;
+        self.size_counts
+            .iter_mut()
+            .for_each(|counts| counts.fill(0));
+        self.jeroslow_wang.fill(0.0);
+        self.heap.clear();
+        self.counted_trail.clear();
+        self.counted_true.iter_mut().for_each(|t| *t = false);
+        self.stale = false;
+
+        for code in 0..self.unresolved.len() {
+            let literal = Literal::from_code(code);
+            let binary_count = bcp.watch.binary_clauses(literal).count() as u32;
+            self.unresolved[code] = binary_count;
+            if self.count_sizes {
+                self.size_counts[code][2] = binary_count;
+                self.jeroslow_wang[code] = binary_count as f64 * size_weight(2);
+            }
+        }
+        self.add_learned_clauses(&bcp.long_clauses);
+
+        for index in 0..self.scores.len() {
+            let variable = Variable::from_index(index as u32);
+            self.update_score(variable);
+            self.heap.insert(variable, &self.scores);
+        }
+
+        bcp.trail.take_backtracked_length();
+        for step in bcp.trail.steps() {
+            self.assign(step.assigned_literal, bcp);
+        }
+        self.valid = true;
+    }
+
+    /// Counts the long clauses added since the last decision
+    fn add_learned_clauses(&mut self, long_clauses: &LongClauses) {
+        for index in self.true_literals.len()..long_clauses.clauses().len() {
+            self.true_literals.push(NOT_COUNTED);
+            self.false_literals.push(0);
+            if !long_clauses.clauses()[index].header().deleted {
+                self.add_long_clause(index, long_clauses.literals(index));
+            }
+        }
+    }
+
+    fn add_long_clause(&mut self, index: ClauseIndex, literals: &[Literal]) {
+        let count = |counted_true: &[bool], polarity: bool| {
+            literals
+                .iter()
+                .filter(|&&l| counted_true[(if polarity { l } else { !l }).as_index()])
+                .count() as u32
+        };
+        let true_literals = count(&self.counted_true, true);
+        self.true_literals[index] = true_literals;
+        if self.count_sizes {
+            self.false_literals[index] = count(&self.counted_true, false);
+        }
+        for &literal in literals {
+            self.occurrences[literal.as_index()].push(index as u32);
+            if true_literals == 0 {
+                self.change_count(literal, 1);
+            }
+        }
+        if true_literals == 0 {
+            self.change_size_counts(index, literals, 1);
+        }
+        self.occurrence_count += literals.len();
+    }
+
+    /// Stops counting a long clause with the given literals, its occurrences are removed once
+    /// most occurrences are not counted anymore
+    fn remove_long_clause(&mut self, index: ClauseIndex, literals: &[Literal]) {
+        if self.true_literals[index] == 0 {
+            for &literal in literals {
+                self.change_count(literal, -1);
+            }
+            self.change_size_counts(index, literals, -1);
+        }
+        self.true_literals[index] = NOT_COUNTED;
+        self.removed_occurrences += literals.len();
+    }
+
+    fn compact_occurrences(&mut self) {
+        if 2 * self.removed_occurrences <= self.occurrence_count {
+            return;
+        }
+        for occurrences in &mut self.occurrences {
+            occurrences.retain(|&clause| self.true_literals[clause as usize] != NOT_COUNTED);
+        }
+        self.occurrence_count -= self.removed_occurrences;
+        self.removed_occurrences = 0;
+    }
+
+    /// Changes the counts of a binary clause that is unresolved by the counted trail
+    fn add_binary_clause(&mut self, literals: [Literal; 2], change: i32) {
+        if literals.iter().all(|l| !self.counted_true[l.as_index()]) {
+            for literal in literals {
+                self.change_count(literal, change);
+            }
+        }
+    }
+
+    /// Removes the first of the given literals from a reason, which was moved to the binary
+    /// clauses if two literals are left
+    fn strengthen_clause(&mut self, index: ClauseIndex, literals: &[Literal]) {
+        let removed = literals[0];
+        let strengthened = &literals[1..];
+        let true_literals = self.true_literals.get(index).copied();
+
+        if let [a, b] = *strengthened {
+            if true_literals.is_some_and(|t| t != NOT_COUNTED) {
+                self.remove_long_clause(index, literals);
+            }
+            self.add_binary_clause([a, b], 1);
+            self.change_binary_size_counts([a, b], 1);
+            return;
+        }
+
+        // clauses that are not counted yet are counted with their new literals
+        let Some(true_literals) = true_literals.filter(|&t| t != NOT_COUNTED) else {
+            return;
+        };
+        let occurrences = &mut self.occurrences[removed.as_index()];
+        let position = occurrences.iter().position(|&c| c == index as u32).unwrap();
+        occurrences.swap_remove(position);
+        self.occurrence_count -= 1;
+
+        let removed_true = self.counted_true[removed.as_index()];
+        let removed_false = self.count_sizes && self.counted_true[(!removed).as_index()];
+        if true_literals == 0 {
+            self.change_count(removed, -1);
+            self.change_size_counts(index, literals, -1);
+        } else if removed_true {
+            self.true_literals[index] -= 1;
+            if true_literals == 1 {
+                for &literal in strengthened {
+                    self.change_count(literal, 1);
+                }
+            }
+        }
+        if removed_false {
+            self.false_literals[index] -= 1;
+        }
+        if self.true_literals[index] == 0 {
+            self.change_size_counts(index, strengthened, 1);
+        }
+    }
+
+    fn assign(&mut self, literal: Literal, bcp: &BcpContext) {
+        for other in bcp.watch.binary_clauses(literal) {
+            self.add_binary_clause([literal, other], -1);
+            self.change_binary_size_counts([literal, other], -1);
+        }
+
+        if self.count_sizes {
+            for other in bcp.watch.binary_clauses(!literal) {
+                if other != literal {
+                    self.change_binary_size_counts([!literal, other], -1);
+                }
+            }
+            // the clauses with the negated literal lose an unassigned literal
+            for i in 0..self.occurrences[(!literal).as_index()].len() {
+                let clause = self.occurrences[(!literal).as_index()][i] as usize;
+                if self.true_literals[clause] == NOT_COUNTED {
+                    continue;
+                }
+                self.false_literals[clause] += 1;
+                if self.true_literals[clause] == 0 {
+                    let literals = bcp.long_clauses.literals(clause);
+                    let size = literals.len() - self.false_literals[clause] as usize;
+                    self.change_size_count(!literal, (size + 1).min(MAX_COUNTED_SIZE), -1);
+                    self.move_size_counts(literals, !literal, size + 1, size);
+                }
+            }
+        }
+
+        for i in 0..self.occurrences[literal.as_index()].len() {
+            let clause = self.occurrences[literal.as_index()][i] as usize;
+            if self.true_literals[clause] == NOT_COUNTED {
+                continue;
+            }
+            self.true_literals[clause] += 1;
+            if self.true_literals[clause] == 1 {
+                let literals = bcp.long_clauses.literals(clause);
+                for &l in literals {
+                    self.change_count(l, -1);
+                }
+                self.change_size_counts(clause, literals, -1);
+            }
+        }
+        self.counted_trail.push(literal);
+        self.counted_true[literal.as_index()] = true;
+    }
+
+    fn unassign(&mut self, bcp: &BcpContext) {
+        let literal = self.counted_trail.pop().unwrap();
+        self.counted_true[literal.as_index()] = false;
+
+        for other in bcp.watch.binary_clauses(literal) {
+            self.add_binary_clause([literal, other], 1);
+            self.change_binary_size_counts([literal, other], 1);
+        }
+
+        if self.count_sizes {
+            for other in bcp.watch.binary_clauses(!literal) {
+                if other != literal {
+                    self.change_binary_size_counts([!literal, other], 1);
+                }
+            }
+            // the clauses with the negated literal gain an unassigned literal
+            for i in 0..self.occurrences[(!literal).as_index()].len() {
+                let clause = self.occurrences[(!literal).as_index()][i] as usize;
+                if self.true_literals[clause] == NOT_COUNTED {
+                    continue;
+                }
+                self.false_literals[clause] -= 1;
+                if self.true_literals[clause] == 0 {
+                    let literals = bcp.long_clauses.literals(clause);
+                    let size = literals.len() - self.false_literals[clause] as usize;
+                    self.move_size_counts(literals, !literal, size - 1, size);
+                    self.change_size_count(!literal, size.min(MAX_COUNTED_SIZE), 1);
+                }
+            }
+        }
+
+        for i in 0..self.occurrences[literal.as_index()].len() {
+            let clause = self.occurrences[literal.as_index()][i] as usize;
+            if self.true_literals[clause] == NOT_COUNTED {
+                continue;
+            }
+            self.true_literals[clause] -= 1;
+            if self.true_literals[clause] == 0 {
+                let literals = bcp.long_clauses.literals(clause);
+                for &l in literals {
+                    self.change_count(l, 1);
+                }
+                self.change_size_counts(clause, literals, 1);
+            }
+        }
+        self.heap.insert(literal.variable(), &self.scores);
+    }
+
+    fn is_unassigned(&self, literal: Literal) -> bool {
+        !self.counted_true[literal.as_index()] && !self.counted_true[(!literal).as_index()]
+    }
+
+    /// Changes the counts by size of the unassigned literals of an unresolved long clause
+    fn change_size_counts(&mut self, index: ClauseIndex, literals: &[Literal], change: i32) {
+        if !self.count_sizes {
+            return;
+        }
+        let size = (literals.len() - self.false_literals[index] as usize).min(MAX_COUNTED_SIZE);
+        for &literal in literals {
+            if self.is_unassigned(literal) {
+                self.change_size_count(literal, size, change);
+            }
+        }
+    }
+
+    /// Moves the counts of the unassigned literals of an unresolved long clause except the given
+    /// one to the new number of unassigned literals of the clause
+    fn move_size_counts(&mut self, literals: &[Literal], except: Literal, from: usize, to: usize) {
+        let (from, to) = (from.min(MAX_COUNTED_SIZE), to.min(MAX_COUNTED_SIZE));
+        if from == to {
+            return;
+        }
+        for &literal in literals {
+            if literal != except && self.is_unassigned(literal) {
+                self.change_size_count(literal, from, -1);
+                self.change_size_count(literal, to, 1);
+            }
+        }
+    }
+
+    /// Changes the counts by size of a binary clause if both literals are unassigned
+    fn change_binary_size_counts(&mut self, literals: [Literal; 2], change: i32) {
+        if self.count_sizes && literals.iter().all(|&l| self.is_unassigned(l)) {
+            for literal in literals {
+                self.change_size_count(literal, 2, change);
+            }
+        }
+    }
+
+    fn change_size_count(&mut self, literal: Literal, size: usize, change: i32) {
+        let count = &mut self.size_counts[literal.as_index()][size];
+        *count = count.wrapping_add_signed(change);
+        self.jeroslow_wang[literal.as_index()] += change as f64 * size_weight(size);
+    }
+
+    fn change_count(&mut self, literal: Literal, change: i32) {
+        let count = &mut self.unresolved[literal.as_index()];
+        *count = count.wrapping_add_signed(change);
+
+        let variable = literal.variable();
+        self.scores[variable.as_index()] = self.score(variable);
+        if change > 0 {
+            self.heap.increased(variable, &self.scores);
+        } else {
+            self.heap.decreased(variable, &self.scores);
+        }
+    }
+
+    /// Updates the score of the variable after its priority changed
+    fn update_score(&mut self, variable: Variable) {
+        let score = self.score(variable);
+        let old_score = std::mem::replace(&mut self.scores[variable.as_index()], score);
+        if score > old_score {
+            self.heap.increased(variable, &self.scores);
+        } else {
+            self.heap.decreased(variable, &self.scores);
         }
     }
 
+    /// Variables that occur in unresolved clauses score at least one, so that they are preferred
+    /// over the others regardless of their priorities
+    fn score(&self, variable: Variable) -> f64 {
+        let positive = Literal::from_variable(&variable, true);
+        let count =
+            self.unresolved[positive.as_index()].max(self.unresolved[(!positive).as_index()]);
+        let score = if count == 0 {
+            0.0
+        } else {
+            (count as f64 + self.priorities[variable.as_index()]).max(1.0)
+        };
+        score - variable.as_index() as f64 / self.scores.len() as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyze::{analyze, ConflictAnalysis};
+    use crate::assignment::VariableAssignment;
+    use crate::bcp::trail::{backtrack, decide_and_assign};
+    use crate::bcp::watch::Watchlists;
+    use crate::bcp::{propagate, BcpContext};
+    use crate::cnf::CNF;
+
+    use super::*;
+
+    /// Counts the unresolved clauses of the unassigned literals by size anew
     fn calculate_literal_scores(
-        &mut self,
+        dlis: &mut Dlis,
         unassigned_literals: Vec<Literal>,
         assignment: &VariableAssignment,
         long: &LongClauses,
-        binary: &BinaryClauses,
+        watch: &Watchlists,
     ) {
-        self.lit_scores.iter_mut().for_each(|s| *s = 0);
+        let counts = &mut dlis.counters.size_counts;
+        counts.iter_mut().for_each(|c| c.fill(0));
 
         for l in unassigned_literals {
-            self.lit_scores[l.as_index()] += binary.unresolved_clauses_count(l, assignment);
+            counts[l.as_index()][2] += watch.unresolved_binary_clauses_count(l, assignment);
         }
 
         for c in long.clauses() {
-            if c.literals().iter().any(|l| assignment.literal_is_true(*l)) {
+            if c.header().deleted || c.literals().iter().any(|l| assignment.literal_is_true(*l)) {
                 continue;
             }
+            let size = c
+                .literals()
+                .iter()
+                .filter(|l| assignment.literal_is_unknown(**l))
+                .count();
             for l in c.literals() {
                 if assignment.literal_is_unknown(*l) {
-                    self.lit_scores[l.as_index()] += 1;
+                    counts[l.as_index()][size.min(MAX_COUNTED_SIZE)] += 1;
                 }
             }
         }
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use crate::bcp::BcpContext;
-    use crate::cnf::CNF;
 
-    use super::*;
+        let counters = &mut dlis.counters;
+        for (sum, counts) in counters.jeroslow_wang.iter_mut().zip(&counters.size_counts) {
+            *sum = (0..=MAX_COUNTED_SIZE)
+                .map(|size| counts[size] as f64 * size_weight(size))
+                .sum();
+        }
+    }
 
     #[test]
     fn test_literal_is_positive() {
@@ -104,9 +774,7 @@ mod tests {
         let mut dlis = Dlis::default();
         dlis.resize(2);
 
-        let decision = dlis
-            .decide(&bcp.assignment, &bcp.long_clauses, &bcp.binary_clauses)
-            .unwrap();
+        let decision = dlis.decide(Scoring::Dlis, &mut bcp).unwrap();
 
         assert_eq!(decision, Literal::from_dimacs(1));
     }
@@ -121,9 +789,7 @@ mod tests {
         let mut dlis = Dlis::default();
         dlis.resize(2);
 
-        let decision = dlis
-            .decide(&bcp.assignment, &bcp.long_clauses, &bcp.binary_clauses)
-            .unwrap();
+        let decision = dlis.decide(Scoring::Dlis, &mut bcp).unwrap();
 
         assert_eq!(decision, Literal::from_dimacs(-1));
     }
@@ -140,7 +806,7 @@ mod tests {
         let mut dlis = Dlis::default();
         dlis.resize(2);
 
-        let decision = dlis.decide(&bcp.assignment, &bcp.long_clauses, &bcp.binary_clauses);
+        let decision = dlis.decide(Scoring::Dlis, &mut bcp);
 
         assert!(decision.is_none());
     }
@@ -163,13 +829,176 @@ mod tests {
             Literal::from_dimacs(-4),
         ];
 
-        dlis.calculate_literal_scores(
+        calculate_literal_scores(
+            &mut dlis,
             unassigned,
             &bcp.assignment,
             &bcp.long_clauses,
-            &bcp.binary_clauses,
+            &bcp.watch,
+        );
+
+        let scores = (0..8)
+            .map(|code| dlis.total_count(Literal::from_code(code)))
+            .collect::<Vec<_>>();
+        assert_eq!(scores, vec![0, 0, 1, 1, 2, 0, 1, 0])
+    }
+
+    fn decide(dimacs: &str, scoring: Scoring) -> i32 {
+        let cnf = CNF::from_dimacs(dimacs);
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut dlis = Dlis::default();
+        dlis.resize(cnf.variable_count());
+        dlis.decide(scoring, &mut bcp).unwrap().as_dimacs_integer()
+    }
+
+    #[test]
+    fn test_jeroslow_wang() {
+        let dimacs = "1 2 0\n-3 4 5 6 0\n-3 5 6 7 0\n-3 4 7 8 0\n3 8 9 10 0\n3 8 9 11 0\n";
+        // -3 occurs most often, but only in long clauses
+        assert_eq!(decide(dimacs, Scoring::Dlis), -3);
+        assert_eq!(decide(dimacs, Scoring::JeroslowWang), 1);
+        // the long clauses with 3 and -3 outweigh the binary clause
+        assert_eq!(decide(dimacs, Scoring::TwoSidedJeroslowWang), -3);
+    }
+
+    #[test]
+    fn test_moms_and_bohm() {
+        let dimacs = "1 2 0\n1 3 0\n1 5 0\n4 6 0\n-4 7 0\n4 8 9 0\n";
+        // 1 has the most binary clauses
+        assert_eq!(decide(dimacs, Scoring::Dlis), 1);
+        assert_eq!(decide(dimacs, Scoring::Moms), 1);
+        // both polarities of 4 in binary clauses count as much as the three clauses of 1, the
+        // ternary clause breaks the tie
+        assert_eq!(decide(dimacs, Scoring::Bohm), 4);
+    }
+
+    /// Asserts that the maintained counts of the unassigned literals are the counts of a rescan
+    fn assert_counts_follow_trail(dlis: &mut Dlis, bcp: &mut BcpContext) {
+        dlis.decide(Scoring::Dlis, bcp);
+        let unresolved = dlis.counters.unresolved.clone();
+        // the counts by size are maintained from the first decision that uses them
+        dlis.decide(Scoring::Bohm, bcp);
+
+        let mut rescan = Dlis::default();
+        rescan.resize(bcp.assignment.variable_count());
+        let unassigned = (0..bcp.assignment.variable_count() * 2)
+            .map(Literal::from_code)
+            .filter(|l| bcp.assignment.literal_is_unknown(*l))
+            .collect::<Vec<_>>();
+        calculate_literal_scores(
+            &mut rescan,
+            unassigned.clone(),
+            &bcp.assignment,
+            &bcp.long_clauses,
+            &bcp.watch,
         );
+        for literal in unassigned {
+            assert_eq!(unresolved[literal.as_index()], rescan.total_count(literal));
+            for size in 0..=MAX_COUNTED_SIZE {
+                assert_eq!(dlis.count(size, literal), rescan.count(size, literal));
+            }
+            assert_eq!(dlis.jeroslow_wang(literal), rescan.jeroslow_wang(literal));
+        }
+    }
+
+    #[test]
+    fn test_counters_follow_trail() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n-1 2 4 0\n-2 3 4 0\n1 -3 0\n-4 5 6 7 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut dlis = Dlis::default();
+        dlis.resize(7);
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+
+        for literal in [-5, 3, -6] {
+            decide_and_assign(&mut bcp, Literal::from_dimacs(literal));
+            propagate(&mut bcp).unwrap();
+            assert_counts_follow_trail(&mut dlis, &mut bcp);
+        }
+
+        backtrack(&mut bcp, 1);
+        let learned = [-4, -7].map(Literal::from_dimacs);
+        bcp.learn_clause(&learned);
+        dlis.add_binary_clause(learned);
+        decide_and_assign(&mut bcp, Literal::from_dimacs(4));
+        propagate(&mut bcp).unwrap();
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+        assert!(dlis.counters.valid);
+    }
+
+    #[test]
+    fn test_counters_follow_trail_after_reset() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n-1 2 4 0\n-2 3 4 0\n1 -3 0\n-4 5 6 7 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut dlis = Dlis::default();
+        dlis.resize(7);
+        for literal in [-5, 3] {
+            decide_and_assign(&mut bcp, Literal::from_dimacs(literal));
+            propagate(&mut bcp).unwrap();
+        }
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+
+        // another heuristic followed the backtracking and the new decisions
+        backtrack(&mut bcp, 0);
+        for literal in [5, -2] {
+            decide_and_assign(&mut bcp, Literal::from_dimacs(literal));
+            propagate(&mut bcp).unwrap();
+        }
+        bcp.trail.take_backtracked_length();
+        dlis.reset_search();
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+        assert!(dlis.counters.valid);
+    }
+
+    #[test]
+    fn test_counters_follow_deleted_clauses() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n-1 2 4 0\n-2 3 4 0\n1 -3 0\n-4 5 6 7 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut dlis = Dlis::default();
+        dlis.resize(7);
+        decide_and_assign(&mut bcp, Literal::from_dimacs(-5));
+        propagate(&mut bcp).unwrap();
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+
+        // the learned clause is counted on the next decision and deleted before the one after it
+        let learned = [-2, -4, 6, 7].map(Literal::from_dimacs);
+        bcp.learn_clause(&learned);
+        decide_and_assign(&mut bcp, Literal::from_dimacs(3));
+        propagate(&mut bcp).unwrap();
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+
+        let deleted = [0, bcp.long_clauses.clauses().len() - 1];
+        for index in deleted {
+            bcp.long_clauses.delete_clause(index);
+        }
+        dlis.delete_clauses(&deleted, &bcp.long_clauses);
+        backtrack(&mut bcp, 0);
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+        assert!(dlis.counters.valid);
+    }
+
+    #[test]
+    fn test_counters_follow_strengthened_reasons() {
+        // the reason of -6 is strengthened to -2 -3 -8 by the conflict after deciding 1
+        let cnf = CNF::from_dimacs("-1 2 0\n-1 3 0\n-1 8 0\n-2 -3 -8 4 0\n-4 6 0\n-6 -2 -3 -8 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut dlis = Dlis::default();
+        dlis.resize(8);
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+
+        decide_and_assign(&mut bcp, Literal::from_dimacs(1));
+        let conflict = propagate(&mut bcp).unwrap_err();
+        let mut analysis = ConflictAnalysis::default();
+        analyze(conflict, &mut analysis, &mut bcp);
+        assert_eq!(analysis.strengthened_reasons().len(), 1);
+        for (index, literals) in analysis.strengthened_reasons() {
+            dlis.strengthen_clause(*index, literals);
+        }
 
-        assert_eq!(dlis.lit_scores, vec![0, 0, 1, 1, 2, 0, 1, 0])
+        propagate(&mut bcp).unwrap();
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+        decide_and_assign(&mut bcp, Literal::from_dimacs(2));
+        propagate(&mut bcp).unwrap();
+        assert_counts_follow_trail(&mut dlis, &mut bcp);
+        assert!(dlis.counters.valid);
     }
 }
@@ -0,0 +1,142 @@
+use crate::literal::Variable;
+
+/// Marks variables that are not in the heap
+const NOT_IN_HEAP: usize = usize::MAX;
+
+/// Binary max-heap of variables ordered by externally stored scores
+#[derive(Default, Debug)]
+pub struct VariableHeap {
+    heap: Vec<Variable>,
+    /// maps a variable to its position in `heap`
+    positions: Vec<usize>,
+}
+
+impl VariableHeap {
+    pub fn resize(&mut self, var_count: usize) {
+        self.positions.resize(var_count, NOT_IN_HEAP);
+    }
+
+    pub fn contains(&self, variable: Variable) -> bool {
+        self.positions[variable.as_index()] != NOT_IN_HEAP
+    }
+
+    pub fn clear(&mut self) {
+        for variable in self.heap.drain(..) {
+            self.positions[variable.as_index()] = NOT_IN_HEAP;
+        }
+    }
+
+    pub fn insert(&mut self, variable: Variable, scores: &[f64]) {
+        if self.contains(variable) {
+            return;
+        }
+        self.positions[variable.as_index()] = self.heap.len();
+        self.heap.push(variable);
+        self.sift_up(self.heap.len() - 1, scores);
+    }
+
+    /// Restores the heap property after the score of the variable was increased
+    pub fn increased(&mut self, variable: Variable, scores: &[f64]) {
+        if self.contains(variable) {
+            self.sift_up(self.positions[variable.as_index()], scores);
+        }
+    }
+
+    /// Restores the heap property after the score of the variable was decreased
+    pub fn decreased(&mut self, variable: Variable, scores: &[f64]) {
+        if self.contains(variable) {
+            self.sift_down(self.positions[variable.as_index()], scores);
+        }
+    }
+
+    /// Returns the variable with the highest score without removing it
+    pub fn peek(&self) -> Option<Variable> {
+        self.heap.first().copied()
+    }
+
+    /// Removes and returns the variable with the highest score
+    pub fn pop(&mut self, scores: &[f64]) -> Option<Variable> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let top = self.heap.swap_remove(0);
+        self.positions[top.as_index()] = NOT_IN_HEAP;
+
+        if let Some(&first) = self.heap.first() {
+            self.positions[first.as_index()] = 0;
+            self.sift_down(0, scores);
+        }
+
+        Some(top)
+    }
+
+    fn sift_up(&mut self, mut position: usize, scores: &[f64]) {
+        let variable = self.heap[position];
+        while position > 0 {
+            let parent = (position - 1) / 2;
+            if scores[self.heap[parent].as_index()] >= scores[variable.as_index()] {
+                break;
+            }
+            self.place(position, self.heap[parent]);
+            position = parent;
+        }
+        self.place(position, variable);
+    }
+
+    fn sift_down(&mut self, mut position: usize, scores: &[f64]) {
+        let variable = self.heap[position];
+        loop {
+            let left = 2 * position + 1;
+            if left >= self.heap.len() {
+                break;
+            }
+            let right = left + 1;
+            let child = if right < self.heap.len()
+                && scores[self.heap[right].as_index()] > scores[self.heap[left].as_index()]
+            {
+                right
+            } else {
+                left
+            };
+            if scores[self.heap[child].as_index()] <= scores[variable.as_index()] {
+                break;
+            }
+            self.place(position, self.heap[child]);
+            position = child;
+        }
+        self.place(position, variable);
+    }
+
+    fn place(&mut self, position: usize, variable: Variable) {
+        self.heap[position] = variable;
+        self.positions[variable.as_index()] = position;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pop_in_score_order() {
+        let mut scores = vec![3.0, 1.0, 4.0, 1.5, 5.0];
+        let mut heap = VariableHeap::default();
+        heap.resize(5);
+        for index in 0..5 {
+            heap.insert(Variable::from_index(index), &scores);
+        }
+
+        scores[1] = 10.0;
+        heap.increased(Variable::from_index(1), &scores);
+        scores[4] = 0.5;
+        heap.decreased(Variable::from_index(4), &scores);
+        assert_eq!(heap.peek(), Some(Variable::from_index(1)));
+
+        let order = std::iter::from_fn(|| heap.pop(&scores))
+            .map(|v| v.index())
+            .collect::<Vec<_>>();
+        assert_eq!(order, vec![1, 2, 0, 3, 4]);
+        assert!(!heap.contains(Variable::from_index(1)));
+    }
+}
@@ -0,0 +1,228 @@
+use std::fmt;
+
+use crate::assignment::{AssignedValue, VariableAssignment};
+use crate::bcp::trail::Trail;
+use crate::literal::{Literal, Variable};
+use crate::resize::Resize;
+
+/// A decision heuristic provided by the user of the solver.
+///
+/// The solver reports the changes of the trail before every decision, so the hooks see all
+/// assignments in trail order and all unassignments in reverse trail order, even if they happened
+/// between two decisions or while the other mode was searching. After every conflict the
+/// heuristic gets the variables that took part in it. Priorities set by the user are reported
+/// before the next decision and should raise the scores of the variables.
+///
+/// The hooks get the variables of the search, which include auxiliary variables. They differ from
+/// the variables of the user only for variables added after bounded variable addition.
+pub trait DecisionHeuristic: fmt::Debug {
+    /// Called whenever the number of variables grows
+    fn resize(&mut self, _variable_count: usize) {}
+
+    /// The literal was assigned true
+    fn assigned(&mut self, _literal: Literal) {}
+
+    /// The literal, which was true, was unassigned by backtracking
+    fn unassigned(&mut self, _literal: Literal) {}
+
+    /// The variables were resolved on or are in the learned clause of a conflict
+    fn conflict(&mut self, _variables: &[Variable]) {}
+
+    /// The user set the priority of the variable, which replaces its previous priority
+    fn prioritized(&mut self, _variable: Variable, _priority: f64) {}
+
+    /// Returns the literal of an unassigned variable to assign as the next decision, or `None`
+    /// if all variables are assigned
+    fn decide(&mut self, context: &DecisionContext) -> Option<Literal>;
+}
+
+impl<H: DecisionHeuristic + ?Sized> DecisionHeuristic for Box<H> {
+    fn resize(&mut self, variable_count: usize) {
+        (**self).resize(variable_count)
+    }
+
+    fn assigned(&mut self, literal: Literal) {
+        (**self).assigned(literal)
+    }
+
+    fn unassigned(&mut self, literal: Literal) {
+        (**self).unassigned(literal)
+    }
+
+    fn conflict(&mut self, variables: &[Variable]) {
+        (**self).conflict(variables)
+    }
+
+    fn prioritized(&mut self, variable: Variable, priority: f64) {
+        (**self).prioritized(variable, priority)
+    }
+
+    fn decide(&mut self, context: &DecisionContext) -> Option<Literal> {
+        (**self).decide(context)
+    }
+}
+
+/// The state of the search visible to a decision heuristic
+pub struct DecisionContext<'a> {
+    assignment: &'a VariableAssignment,
+    trail: &'a Trail,
+}
+
+impl DecisionContext<'_> {
+    pub fn variable_count(&self) -> usize {
+        self.assignment.variable_count()
+    }
+
+    /// Returns the value of the literal or `None` if it is unassigned
+    pub fn value(&self, literal: Literal) -> Option<bool> {
+        match self.assignment.literal_value(literal) {
+            AssignedValue::True => Some(true),
+            AssignedValue::False => Some(false),
+            AssignedValue::Unknown => None,
+        }
+    }
+
+    pub fn is_unassigned(&self, variable: Variable) -> bool {
+        self.assignment.is_unknown(variable)
+    }
+
+    pub fn decision_level(&self) -> u32 {
+        self.trail.current_decision_level()
+    }
+}
+
+/// A user-provided heuristic with the trail it was notified of
+#[derive(Debug)]
+pub struct CustomHeuristic {
+    heuristic: Box<dyn DecisionHeuristic>,
+    notified_trail: Vec<Literal>,
+    /// the trail changed while the heuristic was not following it
+    stale: bool,
+}
+
+impl Resize for CustomHeuristic {
+    fn resize(&mut self, var_count: usize) {
+        self.heuristic.resize(var_count);
+    }
+}
+
+impl CustomHeuristic {
+    pub fn new(heuristic: Box<dyn DecisionHeuristic>) -> CustomHeuristic {
+        CustomHeuristic {
+            heuristic,
+            notified_trail: vec![],
+            stale: false,
+        }
+    }
+
+    pub fn conflict(&mut self, variables: &[Variable]) {
+        self.heuristic.conflict(variables);
+    }
+
+    pub fn prioritize(&mut self, variable: Variable, priority: f64) {
+        self.heuristic.prioritized(variable, priority);
+    }
+
+    /// The trail changes without the heuristic following them, e.g. while the other mode is
+    /// searching
+    pub fn reset_search(&mut self) {
+        self.stale = true;
+    }
+
+    /// Reports the changes of the trail since the last decision and asks for the next decision
+    pub fn decide(
+        &mut self,
+        assignment: &VariableAssignment,
+        trail: &mut Trail,
+    ) -> Option<Literal> {
+        self.follow_trail(trail);
+
+        let context = DecisionContext { assignment, trail };
+        let decision = self.heuristic.decide(&context)?;
+        assert!(
+            assignment.literal_is_unknown(decision),
+            "decision heuristic chose the assigned literal {decision}"
+        );
+        Some(decision)
+    }
+
+    fn follow_trail(&mut self, trail: &mut Trail) {
+        let backtracked_length = trail.take_backtracked_length();
+        let kept = if std::mem::take(&mut self.stale) {
+            self.notified_trail
+                .iter()
+                .zip(trail.steps())
+                .take_while(|(&literal, step)| literal == step.assigned_literal)
+                .count()
+        } else {
+            backtracked_length.min(self.notified_trail.len())
+        };
+
+        while self.notified_trail.len() > kept {
+            let literal = self.notified_trail.pop().unwrap();
+            self.heuristic.unassigned(literal);
+        }
+        for step in &trail.steps()[kept..] {
+            self.notified_trail.push(step.assigned_literal);
+            self.heuristic.assigned(step.assigned_literal);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bcp::trail;
+    use crate::bcp::BcpContext;
+    use crate::cnf::CNF;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records the hooks as DIMACS literals, unassignments are prefixed by zero
+    #[derive(Debug, Default)]
+    struct Recorder {
+        events: Rc<RefCell<Vec<i32>>>,
+    }
+
+    impl DecisionHeuristic for Recorder {
+        fn assigned(&mut self, literal: Literal) {
+            self.events.borrow_mut().push(literal.as_dimacs_integer());
+        }
+
+        fn unassigned(&mut self, literal: Literal) {
+            self.events
+                .borrow_mut()
+                .extend([0, literal.as_dimacs_integer()]);
+        }
+
+        fn decide(&mut self, _context: &DecisionContext) -> Option<Literal> {
+            None
+        }
+    }
+
+    #[test]
+    fn test_hooks_follow_trail() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let recorder = Recorder::default();
+        let events = recorder.events.clone();
+        let mut custom = CustomHeuristic::new(Box::new(recorder));
+        let [a, b, c] = [1, -2, 3].map(Literal::from_dimacs);
+
+        trail::decide_and_assign(&mut bcp, a);
+        trail::decide_and_assign(&mut bcp, b);
+        custom.decide(&bcp.assignment, &mut bcp.trail);
+        trail::backtrack(&mut bcp, 0);
+        trail::decide_and_assign(&mut bcp, c);
+        custom.decide(&bcp.assignment, &mut bcp.trail);
+        assert_eq!(*events.borrow(), vec![1, -2, 0, -2, 0, 1, 3]);
+
+        // without the backtracked length the changes are found by comparing the trails
+        custom.reset_search();
+        trail::backtrack(&mut bcp, 0);
+        trail::decide_and_assign(&mut bcp, a);
+        bcp.trail.take_backtracked_length();
+        custom.decide(&bcp.assignment, &mut bcp.trail);
+        assert_eq!(events.borrow()[7..], [0, 3, 1]);
+    }
+}
@@ -0,0 +1,155 @@
+use crate::assignment::VariableAssignment;
+use crate::literal::Variable;
+use crate::resize::Resize;
+
+/// Marks variables that are not in the decision order
+const NOT_ORDERED: usize = usize::MAX;
+
+/// Decisions and priorities requested by the user.
+///
+/// The variables of the decision order are decided first, in the given order, before the decision
+/// heuristic is asked. Priorities raise the scores of the decision heuristics instead, which get
+/// the changed priorities before the next decision. Both are kept across incremental solves.
+#[derive(Default, Debug)]
+pub struct DecisionHints {
+    order: Vec<Variable>,
+    /// maps a variable to its position in `order`
+    positions: Vec<usize>,
+    /// all variables of `order` before this position are assigned
+    next_position: usize,
+    /// maps a variable to its priority
+    priorities: Vec<f64>,
+    /// maps a variable to the priority the decision heuristics got last
+    applied_priorities: Vec<f64>,
+    /// variables whose priority was set since the decision heuristics got them
+    changed: Vec<Variable>,
+}
+
+impl Resize for DecisionHints {
+    fn resize(&mut self, var_count: usize) {
+        self.positions.resize(var_count, NOT_ORDERED);
+        self.priorities.resize(var_count, 0.0);
+        self.applied_priorities.resize(var_count, 0.0);
+    }
+}
+
+impl DecisionHints {
+    /// Replaces the variables that are decided first, repeated variables are decided at their
+    /// first position
+    pub fn set_order(&mut self, order: &[Variable]) {
+        for variable in self.order.drain(..) {
+            self.positions[variable.as_index()] = NOT_ORDERED;
+        }
+        for &variable in order {
+            if self.positions[variable.as_index()] == NOT_ORDERED {
+                self.positions[variable.as_index()] = self.order.len();
+                self.order.push(variable);
+            }
+        }
+        self.next_position = 0;
+    }
+
+    pub fn set_priority(&mut self, variable: Variable, priority: f64) {
+        self.priorities[variable.as_index()] = priority;
+        self.changed.push(variable);
+    }
+
+    /// Returns the variables whose priority changed since the last call with the priority the
+    /// decision heuristics got before and the new one
+    pub fn take_changed_priorities(&mut self) -> Vec<(Variable, f64, f64)> {
+        let mut changes = vec![];
+        for variable in std::mem::take(&mut self.changed) {
+            let index = variable.as_index();
+            let priority = self.priorities[index];
+            let previous = std::mem::replace(&mut self.applied_priorities[index], priority);
+            if previous != priority {
+                changes.push((variable, previous, priority));
+            }
+        }
+        changes
+    }
+
+    /// Returns the variables with the nonzero priorities the decision heuristics got
+    pub fn applied_priorities(&self) -> Vec<(Variable, f64)> {
+        (0..self.applied_priorities.len())
+            .map(|index| {
+                (
+                    Variable::from_index(index as u32),
+                    self.applied_priorities[index],
+                )
+            })
+            .filter(|&(_, priority)| priority != 0.0)
+            .collect()
+    }
+
+    pub fn is_ordered(&self, variable: Variable) -> bool {
+        self.positions[variable.as_index()] != NOT_ORDERED
+    }
+
+    /// Makes an unassigned variable of the order available for decisions again
+    pub fn unassigned(&mut self, variable: Variable) {
+        self.next_position = self.next_position.min(self.positions[variable.as_index()]);
+    }
+
+    /// Returns the first unassigned variable of the order, or `None` if the decision heuristic
+    /// decides next
+    pub fn decide(&mut self, assignment: &VariableAssignment) -> Option<Variable> {
+        while let Some(&variable) = self.order.get(self.next_position) {
+            if assignment.is_unknown(variable) {
+                return Some(variable);
+            }
+            self.next_position += 1;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::literal::Literal;
+
+    #[test]
+    fn test_decide_in_order() {
+        let mut assignment = VariableAssignment::default();
+        assignment.resize(4);
+        let [a, b, c, d] = [0, 1, 2, 3].map(Variable::from_index);
+        let mut hints = DecisionHints::default();
+        hints.resize(4);
+        hints.set_order(&[d, b, d, a]);
+        assert!(hints.is_ordered(a) && !hints.is_ordered(c));
+
+        let mut decisions = vec![];
+        while let Some(variable) = hints.decide(&assignment) {
+            decisions.push(variable);
+            assignment.assign_true(Literal::from_variable(&variable, true));
+        }
+        assert_eq!(decisions, vec![d, b, a]);
+
+        // an unassigned variable is decided again, before the later ones
+        assignment.assign_unknown(b);
+        assignment.assign_unknown(a);
+        hints.unassigned(a);
+        hints.unassigned(b);
+        assert_eq!(hints.decide(&assignment), Some(b));
+    }
+
+    #[test]
+    fn test_changed_priorities() {
+        let [a, b, c] = [0, 1, 2].map(Variable::from_index);
+        let mut hints = DecisionHints::default();
+        hints.resize(3);
+        hints.set_priority(a, 1.0);
+        hints.set_priority(b, 2.0);
+        hints.set_priority(a, 3.0);
+        hints.set_priority(c, 0.0);
+        assert_eq!(
+            hints.take_changed_priorities(),
+            vec![(a, 0.0, 3.0), (b, 0.0, 2.0)]
+        );
+
+        hints.set_priority(b, 1.0);
+        assert_eq!(hints.take_changed_priorities(), vec![(b, 2.0, 1.0)]);
+        assert!(hints.take_changed_priorities().is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use crate::assignment::VariableAssignment;
+use crate::literal::Variable;
+use crate::resize::Resize;
+use crate::search::heap::VariableHeap;
+
+/// Step size of the moving averages at the start, it decreases with every conflict
+const INITIAL_STEP_SIZE: f64 = 0.4;
+
+/// Decrease of the step size per conflict
+const STEP_SIZE_DECREMENT: f64 = 1e-6;
+
+/// The step size does not decrease below this value
+const MIN_STEP_SIZE: f64 = 0.06;
+
+/// Learning-rate-based decision heuristic, the queue of CHB and LRB (Liang et al. 2016).
+///
+/// The score of a variable is an exponential recency weighted average of the rewards it got from
+/// the conflict analysis, which decide whether CHB or LRB is used. Unassigned variables are kept
+/// in a heap ordered by their scores, assigned variables are removed lazily at decisions.
+#[derive(Default, Debug)]
+pub struct LearningRate {
+    scores: Vec<f64>,
+    step_size: f64,
+    heap: VariableHeap,
+}
+
+impl Resize for LearningRate {
+    fn resize(&mut self, var_count: usize) {
+        if self.scores.is_empty() {
+            self.step_size = INITIAL_STEP_SIZE;
+        }
+
+        let old_count = self.scores.len();
+        self.scores.resize(var_count, 0.0);
+        self.heap.resize(var_count);
+        for index in old_count..var_count {
+            self.heap
+                .insert(Variable::from_index(index as u32), &self.scores);
+        }
+    }
+}
+
+impl LearningRate {
+    /// Returns the unassigned variable with the highest score or `None` if all variables are
+    /// assigned
+    pub fn decide(&mut self, assignment: &VariableAssignment) -> Option<Variable> {
+        while let Some(variable) = self.heap.pop(&self.scores) {
+            if assignment.is_unknown(variable) {
+                return Some(variable);
+            }
+        }
+        None
+    }
+
+    /// Returns the unassigned variable with the highest score without removing it from the heap
+    pub fn next_decision(&mut self, assignment: &VariableAssignment) -> Option<Variable> {
+        let variable = self.decide(assignment)?;
+        self.heap.insert(variable, &self.scores);
+        Some(variable)
+    }
+
+    /// Returns true if `a` would be decided before `b`
+    pub fn prefers(&self, a: Variable, b: Variable) -> bool {
+        self.scores[a.as_index()] > self.scores[b.as_index()]
+    }
+
+    /// Moves the scores of the variables towards their rewards by the step size
+    pub fn reward(&mut self, rewards: &[(Variable, f64)]) {
+        for &(variable, reward) in rewards {
+            let score = &mut self.scores[variable.as_index()];
+            let old_score = *score;
+            *score += self.step_size * (reward - old_score);
+            if *score > old_score {
+                self.heap.increased(variable, &self.scores);
+            } else {
+                self.heap.decreased(variable, &self.scores);
+            }
+        }
+    }
+
+    /// Moves the score of the variable towards the highest reward as if it got it in that many
+    /// conflicts, moves it away for negative counts
+    pub fn raise(&mut self, variable: Variable, conflicts: f64) {
+        let score = &mut self.scores[variable.as_index()];
+        let kept = (1.0 - self.step_size).powf(conflicts);
+        *score = (1.0 - (1.0 - *score) * kept).max(0.0);
+        if conflicts > 0.0 {
+            self.heap.increased(variable, &self.scores);
+        } else {
+            self.heap.decreased(variable, &self.scores);
+        }
+    }
+
+    /// Decreases the step size after a conflict, so that the scores become more stable
+    pub fn conflict(&mut self) {
+        self.step_size = (self.step_size - STEP_SIZE_DECREMENT).max(MIN_STEP_SIZE);
+    }
+
+    /// Makes an unassigned variable available for decisions again
+    pub fn unassigned(&mut self, variable: Variable) {
+        self.heap.insert(variable, &self.scores);
+    }
+
+    /// Rebuilds the heap from all unassigned variables
+    pub fn reset_search(&mut self, assignment: &VariableAssignment) {
+        self.heap.clear();
+        for variable in assignment.unassigned() {
+            self.heap.insert(variable, &self.scores);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewards_move_scores() {
+        let mut assignment = VariableAssignment::default();
+        assignment.resize(3);
+        let mut queue = LearningRate::default();
+        queue.resize(3);
+        let [a, b, c] = [0, 1, 2].map(Variable::from_index);
+
+        queue.reward(&[(a, 0.5), (b, 1.0)]);
+        assert_eq!(queue.decide(&assignment), Some(b));
+
+        // repeated low rewards move the score of b below the score of a
+        queue.unassigned(b);
+        queue.reward(&[(b, 0.0), (b, 0.0), (c, 0.1)]);
+        assert!(queue.prefers(a, b));
+        assert_eq!(queue.decide(&assignment), Some(a));
+        assert_eq!(queue.decide(&assignment), Some(b));
+    }
+}
@@ -0,0 +1,375 @@
+use std::fmt;
+
+use crate::analyze::Rewards;
+use crate::assignment::VariableAssignment;
+use crate::literal::Variable;
+use crate::resize::Resize;
+use crate::search::dlis::Scoring;
+use crate::search::heuristic::CustomHeuristic;
+use crate::search::learning_rate::LearningRate;
+use crate::search::policy::RestartPolicy;
+use crate::search::restart::{RestartSchedule, Restarts};
+use crate::search::vmtf::Vmtf;
+use crate::search::vsids::Vsids;
+
+/// Ticks in focused mode before the first switch, the base length of all later modes
+const FIRST_MODE_TICKS: u64 = 1_000_000;
+
+/// The search alternates between two modes (Oh 2015, Biere et al. 2020)
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// frequent restarts and a fast moving decision queue, good at refuting formulas
+    Focused,
+    /// rare restarts and target phases, good at finding models
+    Stable,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Focused => write!(f, "focused"),
+            Mode::Stable => write!(f, "stable"),
+        }
+    }
+}
+
+/// Decision heuristics of a mode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Decisions {
+    /// variable move-to-front queue
+    Vmtf,
+    /// variable activity scores
+    Vsids,
+    /// conflict history-based branching, rewards for recent conflicts while assigned
+    Chb,
+    /// learning rate branching, rewards for the share of conflicts while assigned
+    Lrb,
+    /// dynamic largest individual sum of unresolved clauses
+    Dlis,
+    /// one-sided Jeroslow-Wang, unresolved clauses weighted by `2^-size`
+    JeroslowWang,
+    /// two-sided Jeroslow-Wang, the weighted clauses of both polarities
+    TwoSidedJeroslowWang,
+    /// maximum occurrences in clauses of minimum size
+    Moms,
+    /// Böhm's heuristic, occurrences by clause size compared lexicographically
+    Bohm,
+    /// the unassigned variable with the lowest index
+    FirstUnassigned,
+}
+
+/// The policies used by the search in one mode
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ModePolicy {
+    pub decisions: Decisions,
+    pub restarts: Restarts,
+    /// decide the phases of the longest conflict-free trail instead of the last values
+    pub target_phases: bool,
+}
+
+impl ModePolicy {
+    pub fn focused() -> ModePolicy {
+        ModePolicy {
+            decisions: Decisions::Vmtf,
+            restarts: Restarts::Glue,
+            target_phases: false,
+        }
+    }
+
+    pub fn stable() -> ModePolicy {
+        ModePolicy {
+            decisions: Decisions::Vsids,
+            restarts: Restarts::Luby,
+            target_phases: true,
+        }
+    }
+}
+
+/// The state of a decision heuristic, heuristics without own state are computed by the search,
+/// custom heuristics are provided by the user
+#[derive(Debug)]
+pub enum DecisionQueue {
+    Vmtf(Vmtf),
+    Vsids(Vsids),
+    Chb(LearningRate),
+    Lrb(LearningRate),
+    Scoring(Scoring),
+    FirstUnassigned,
+    Custom(CustomHeuristic),
+}
+
+impl DecisionQueue {
+    fn new(decisions: Decisions) -> DecisionQueue {
+        match decisions {
+            Decisions::Vmtf => DecisionQueue::Vmtf(Default::default()),
+            Decisions::Vsids => DecisionQueue::Vsids(Default::default()),
+            Decisions::Chb => DecisionQueue::Chb(Default::default()),
+            Decisions::Lrb => DecisionQueue::Lrb(Default::default()),
+            Decisions::Dlis => DecisionQueue::Scoring(Scoring::Dlis),
+            Decisions::JeroslowWang => DecisionQueue::Scoring(Scoring::JeroslowWang),
+            Decisions::TwoSidedJeroslowWang => {
+                DecisionQueue::Scoring(Scoring::TwoSidedJeroslowWang)
+            }
+            Decisions::Moms => DecisionQueue::Scoring(Scoring::Moms),
+            Decisions::Bohm => DecisionQueue::Scoring(Scoring::Bohm),
+            Decisions::FirstUnassigned => DecisionQueue::FirstUnassigned,
+        }
+    }
+
+    /// Updates the queue after a conflict involving the given variables
+    pub fn bump(&mut self, variables: &mut [Variable], assignment: &VariableAssignment) {
+        match self {
+            DecisionQueue::Vmtf(vmtf) => vmtf.bump(variables, assignment),
+            DecisionQueue::Vsids(vsids) => vsids.bump(variables),
+            DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) => queue.conflict(),
+            DecisionQueue::Custom(custom) => custom.conflict(variables),
+            DecisionQueue::Scoring(_) | DecisionQueue::FirstUnassigned => {}
+        }
+    }
+
+    /// Raises the score of the variable after the user changed its priority, lower priorities
+    /// lower the scores of the queues with activities or rewards
+    pub fn prioritize(&mut self, variable: Variable, previous: f64, priority: f64) {
+        match self {
+            DecisionQueue::Vmtf(vmtf) => {
+                if priority > previous {
+                    vmtf.raise(variable);
+                }
+            }
+            DecisionQueue::Vsids(vsids) => vsids.raise(variable, priority - previous),
+            DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) => {
+                queue.raise(variable, priority - previous)
+            }
+            DecisionQueue::Custom(custom) => custom.prioritize(variable, priority),
+            DecisionQueue::Scoring(_) | DecisionQueue::FirstUnassigned => {}
+        }
+    }
+
+    pub fn unassigned(&mut self, variable: Variable) {
+        match self {
+            DecisionQueue::Vmtf(vmtf) => vmtf.unassigned(variable),
+            DecisionQueue::Vsids(vsids) => vsids.unassigned(variable),
+            DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) => queue.unassigned(variable),
+            DecisionQueue::Scoring(_)
+            | DecisionQueue::FirstUnassigned
+            | DecisionQueue::Custom(_) => {}
+        }
+    }
+
+    /// Returns the variable the next decision would choose without deciding it, or `None` if the
+    /// queue does not order the variables
+    pub fn next_decision(&mut self, assignment: &VariableAssignment) -> Option<Variable> {
+        match self {
+            DecisionQueue::Vmtf(vmtf) => vmtf.decide(assignment),
+            DecisionQueue::Vsids(vsids) => vsids.next_decision(assignment),
+            DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) => {
+                queue.next_decision(assignment)
+            }
+            DecisionQueue::Scoring(_)
+            | DecisionQueue::FirstUnassigned
+            | DecisionQueue::Custom(_) => None,
+        }
+    }
+
+    /// Returns the rewards the conflict analysis collects for the queue
+    pub fn rewards(&self) -> Rewards {
+        match self {
+            DecisionQueue::Chb(_) => Rewards::Chb,
+            DecisionQueue::Lrb(_) => Rewards::Lrb,
+            _ => Rewards::None,
+        }
+    }
+
+    /// Updates the scores of a learning-rate-based queue by the rewards of the conflict analysis
+    pub fn reward(&mut self, rewards: &[(Variable, f64)]) {
+        if let DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) = self {
+            queue.reward(rewards);
+        }
+    }
+
+    /// Returns true if `a` would be decided before `b`
+    pub fn prefers(&self, a: Variable, b: Variable) -> bool {
+        match self {
+            DecisionQueue::Vmtf(vmtf) => vmtf.prefers(a, b),
+            DecisionQueue::Vsids(vsids) => vsids.prefers(a, b),
+            DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) => queue.prefers(a, b),
+            DecisionQueue::Scoring(_)
+            | DecisionQueue::FirstUnassigned
+            | DecisionQueue::Custom(_) => false,
+        }
+    }
+
+    /// Makes all unassigned variables available for decisions, e.g. after the queue did not
+    /// follow the trail
+    pub fn reset_search(&mut self, assignment: &VariableAssignment) {
+        match self {
+            DecisionQueue::Vmtf(vmtf) => vmtf.reset_search(),
+            DecisionQueue::Vsids(vsids) => vsids.reset_search(assignment),
+            DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) => queue.reset_search(assignment),
+            DecisionQueue::Custom(custom) => custom.reset_search(),
+            DecisionQueue::Scoring(_) | DecisionQueue::FirstUnassigned => {}
+        }
+    }
+}
+
+impl Resize for DecisionQueue {
+    fn resize(&mut self, var_count: usize) {
+        match self {
+            DecisionQueue::Vmtf(vmtf) => vmtf.resize(var_count),
+            DecisionQueue::Vsids(vsids) => vsids.resize(var_count),
+            DecisionQueue::Chb(queue) | DecisionQueue::Lrb(queue) => queue.resize(var_count),
+            DecisionQueue::Custom(custom) => custom.resize(var_count),
+            DecisionQueue::Scoring(_) | DecisionQueue::FirstUnassigned => {}
+        }
+    }
+}
+
+/// The policies of a mode with their own decision queue and restart schedule
+#[derive(Debug)]
+pub struct ModeState {
+    pub policy: ModePolicy,
+    pub queue: DecisionQueue,
+    pub restarts: Box<dyn RestartPolicy>,
+}
+
+impl ModeState {
+    pub fn new(policy: ModePolicy, var_count: usize) -> ModeState {
+        let mut queue = DecisionQueue::new(policy.decisions);
+        queue.resize(var_count);
+        ModeState {
+            policy,
+            queue,
+            restarts: Box::new(RestartSchedule::new(policy.restarts)),
+        }
+    }
+}
+
+/// The state of both modes
+#[derive(Debug)]
+pub struct Modes {
+    pub mode: Mode,
+    pub focused: ModeState,
+    pub stable: ModeState,
+    pub switching: ModeSwitchSchedule,
+}
+
+impl Default for Modes {
+    /// Both modes decide with DLIS by default
+    fn default() -> Self {
+        let dlis = |policy: ModePolicy| ModePolicy {
+            decisions: Decisions::Dlis,
+            ..policy
+        };
+        Modes {
+            mode: Mode::Focused,
+            focused: ModeState::new(dlis(ModePolicy::focused()), 0),
+            stable: ModeState::new(dlis(ModePolicy::stable()), 0),
+            switching: Default::default(),
+        }
+    }
+}
+
+impl Resize for Modes {
+    fn resize(&mut self, var_count: usize) {
+        self.focused.queue.resize(var_count);
+        self.stable.queue.resize(var_count);
+    }
+}
+
+impl Modes {
+    pub fn current(&self) -> &ModeState {
+        self.state(self.mode)
+    }
+
+    pub fn current_mut(&mut self) -> &mut ModeState {
+        match self.mode {
+            Mode::Focused => &mut self.focused,
+            Mode::Stable => &mut self.stable,
+        }
+    }
+
+    pub fn state(&self, mode: Mode) -> &ModeState {
+        match mode {
+            Mode::Focused => &self.focused,
+            Mode::Stable => &self.stable,
+        }
+    }
+
+    /// Replaces the policy of a mode, which starts with a new decision queue and restart schedule
+    pub fn set_policy(&mut self, mode: Mode, policy: ModePolicy, var_count: usize) {
+        let state = ModeState::new(policy, var_count);
+        match mode {
+            Mode::Focused => self.focused = state,
+            Mode::Stable => self.stable = state,
+        }
+    }
+
+    /// Replaces the decision queue of a mode with a custom heuristic, the rest of its policy is
+    /// kept
+    pub fn set_heuristic(&mut self, mode: Mode, heuristic: CustomHeuristic, var_count: usize) {
+        let state = match mode {
+            Mode::Focused => &mut self.focused,
+            Mode::Stable => &mut self.stable,
+        };
+        state.queue = DecisionQueue::Custom(heuristic);
+        state.queue.resize(var_count);
+    }
+
+    /// Replaces the restart schedule of a mode with a custom policy
+    pub fn set_restart_policy(&mut self, mode: Mode, restarts: Box<dyn RestartPolicy>) {
+        match mode {
+            Mode::Focused => self.focused.restarts = restarts,
+            Mode::Stable => self.stable.restarts = restarts,
+        }
+    }
+}
+
+/// Schedules the switches between the modes. The first mode lasts a fixed number of ticks, later
+/// modes last a multiple of it, which grows quadratically with the number of switches.
+#[derive(Debug, Default)]
+pub struct ModeSwitchSchedule {
+    pub switches: u64,
+    /// ticks at the start of the current mode
+    start_ticks: u64,
+    /// switch between the modes at all
+    pub disabled: bool,
+}
+
+impl ModeSwitchSchedule {
+    pub fn is_due(&self, ticks: u64) -> bool {
+        if self.disabled {
+            return false;
+        }
+        let round = self.switches / 2 + 1;
+        ticks - self.start_ticks >= FIRST_MODE_TICKS * round * round
+    }
+
+    pub fn switched(&mut self, ticks: u64) {
+        self.switches += 1;
+        self.start_ticks = ticks;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quadratic_mode_lengths() {
+        let mut schedule = ModeSwitchSchedule::default();
+        assert!(!schedule.is_due(FIRST_MODE_TICKS - 1));
+        assert!(schedule.is_due(FIRST_MODE_TICKS));
+        schedule.switched(FIRST_MODE_TICKS);
+
+        // the stable mode lasts as long as the first focused mode
+        assert!(!schedule.is_due(2 * FIRST_MODE_TICKS - 1));
+        assert!(schedule.is_due(2 * FIRST_MODE_TICKS));
+        schedule.switched(2 * FIRST_MODE_TICKS);
+
+        // the second focused and stable modes last four times as long
+        assert!(!schedule.is_due(6 * FIRST_MODE_TICKS - 1));
+        assert!(schedule.is_due(6 * FIRST_MODE_TICKS));
+        schedule.switched(6 * FIRST_MODE_TICKS);
+        assert!(schedule.is_due(10 * FIRST_MODE_TICKS));
+    }
+}
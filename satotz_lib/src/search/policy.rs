@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// The clause learned from a conflict and the state of the search when it happened
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ConflictInfo {
+    /// number of decision levels in the learned clause (LBD)
+    pub glue: u32,
+    /// number of assignments on the trail at the conflict
+    pub trail_length: usize,
+    /// number of conflicts so far, including this one
+    pub conflicts: u64,
+}
+
+/// Decides when the search restarts from the top decision level. Every mode has its own policy.
+///
+/// The search keeps the decision levels that its decision heuristic would decide again in the
+/// same order, so a restart can end on a higher level.
+pub trait RestartPolicy: fmt::Debug {
+    /// Called after every conflict of the mode
+    fn conflict(&mut self, conflict: &ConflictInfo);
+
+    /// Returns true if the search should restart now, asked before every decision
+    fn should_restart(&mut self) -> bool;
+
+    /// Called after the search restarted
+    fn restarted(&mut self) {}
+}
+
+impl<P: RestartPolicy + ?Sized> RestartPolicy for Box<P> {
+    fn conflict(&mut self, conflict: &ConflictInfo) {
+        (**self).conflict(conflict)
+    }
+
+    fn should_restart(&mut self) -> bool {
+        (**self).should_restart()
+    }
+
+    fn restarted(&mut self) {
+        (**self).restarted()
+    }
+}
+
+/// Decides when the learned clauses are reduced, i.e. when the least useful of them are deleted
+pub trait ClauseReductionPolicy: fmt::Debug {
+    /// Called after every conflict
+    fn conflict(&mut self, conflict: &ConflictInfo);
+
+    /// Returns true if the learned clauses should be reduced now, asked before every decision
+    fn should_reduce(&mut self) -> bool;
+
+    /// Called after a reduction with the number of deleted clauses
+    fn reduced(&mut self, _deleted: usize) {}
+}
+
+impl<P: ClauseReductionPolicy + ?Sized> ClauseReductionPolicy for Box<P> {
+    fn conflict(&mut self, conflict: &ConflictInfo) {
+        (**self).conflict(conflict)
+    }
+
+    fn should_reduce(&mut self) -> bool {
+        (**self).should_reduce()
+    }
+
+    fn reduced(&mut self, deleted: usize) {
+        (**self).reduced(deleted)
+    }
+}
@@ -0,0 +1,135 @@
+use crate::bcp::trail::Reason;
+use crate::bcp::BcpContext;
+use crate::clause::{ClauseIndex, Tier};
+use crate::search::policy::{ClauseReductionPolicy, ConflictInfo};
+
+/// Conflicts before the first reduction
+const FIRST_INTERVAL: u64 = 2_000;
+
+/// The conflict interval between reductions grows by this amount after every reduction
+const INTERVAL_INCREMENT: u64 = 300;
+
+/// Percentage of the local clauses with the lowest activity that are deleted by a reduction
+const LOCAL_DELETE_PERCENT: usize = 50;
+
+/// Schedules reductions of the learned clauses at arithmetically growing conflict intervals
+#[derive(Debug)]
+pub struct ReduceSchedule {
+    /// number of conflicts at which the next reduction is due
+    next_reduction: u64,
+    interval: u64,
+    conflicts: u64,
+}
+
+impl Default for ReduceSchedule {
+    fn default() -> Self {
+        ReduceSchedule {
+            next_reduction: FIRST_INTERVAL,
+            interval: FIRST_INTERVAL,
+            conflicts: 0,
+        }
+    }
+}
+
+impl ClauseReductionPolicy for ReduceSchedule {
+    fn conflict(&mut self, conflict: &ConflictInfo) {
+        self.conflicts = conflict.conflicts;
+    }
+
+    fn should_reduce(&mut self) -> bool {
+        self.conflicts >= self.next_reduction
+    }
+
+    fn reduced(&mut self, _deleted: usize) {
+        self.interval += INTERVAL_INCREMENT;
+        self.next_reduction = self.conflicts + self.interval;
+    }
+}
+
+/// Deletes learned long clauses depending on their tier. Core clauses are kept, tier-2 clauses
+/// are kept if they were used since the last reduction, and the local clauses with the lowest
+/// activity are deleted. Clauses that are reasons of assignments are kept as well. Returns the
+/// indices of the deleted clauses.
+pub fn reduce_learned_clauses(bcp: &mut BcpContext) -> Vec<ClauseIndex> {
+    let mut reasons = vec![false; bcp.long_clauses.clauses().len()];
+    for step in bcp.trail.steps() {
+        if let Reason::Long(index) = step.reason {
+            reasons[index] = true;
+        }
+    }
+
+    let mut deleted = vec![];
+    let mut local = vec![];
+    for (index, clause) in bcp.long_clauses.clauses().iter().enumerate() {
+        let header = clause.header();
+        if !header.learned || header.deleted || reasons[index] {
+            continue;
+        }
+
+        match header.tier() {
+            Tier::Core => {}
+            Tier::Tier2 if header.used => {}
+            Tier::Tier2 => deleted.push(index),
+            Tier::Local => local.push(index),
+        }
+    }
+
+    local.sort_by(|a, b| {
+        let activity = |c: &usize| bcp.long_clauses.clauses()[*c].header().activity;
+        activity(a).total_cmp(&activity(b))
+    });
+    deleted.extend_from_slice(&local[..local.len() * LOCAL_DELETE_PERCENT / 100]);
+
+    for &index in &deleted {
+        bcp.proof.delete_clause(bcp.long_clauses.literals(index));
+        bcp.long_clauses.delete_clause(index);
+    }
+
+    for index in 0..bcp.long_clauses.clauses().len() {
+        bcp.long_clauses.find_clause_mut(index).header_mut().used = false;
+    }
+
+    deleted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_reduce_by_tier() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n1 2 4 0\n1 2 5 0\n1 2 6 0\n1 2 7 0\n1 2 8 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        let headers = [
+            (2, false, 0.0),
+            (6, true, 0.0),
+            (6, false, 5.0),
+            (7, true, 1.0),
+        ];
+        for (index, (glue, used, activity)) in headers.into_iter().enumerate() {
+            let header = bcp.long_clauses.find_clause_mut(index).header_mut();
+            header.learned = true;
+            header.glue = glue;
+            header.used = used;
+            header.activity = activity;
+        }
+        let header = bcp.long_clauses.find_clause_mut(4).header_mut();
+        header.learned = true;
+        header.glue = 10;
+        header.activity = 2.0;
+
+        assert_eq!(reduce_learned_clauses(&mut bcp), vec![2, 3]);
+
+        let deleted = bcp
+            .long_clauses
+            .clauses()
+            .iter()
+            .map(|c| c.header().deleted)
+            .collect::<Vec<_>>();
+        // the unused tier-2 clause and the local clause with the lower activity are deleted
+        assert_eq!(deleted, vec![false, false, true, true, false, false]);
+        assert!(!bcp.long_clauses.clauses()[1].header().used);
+    }
+}
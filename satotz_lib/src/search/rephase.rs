@@ -0,0 +1,149 @@
+use crate::bcp::phases::ORIGINAL_PHASE;
+use crate::bcp::BcpContext;
+use crate::local_search::LocalSearch;
+use crate::random::XorShift;
+
+/// Conflicts between the first rephases, the interval grows by this amount after every rephase
+const REPHASE_INTERVAL: u64 = 1_000;
+
+/// Local search may use this percentage of the search ticks since the last walk as flips
+const WALK_EFFORT_PERCENT: u64 = 10;
+
+/// Minimum number of flips of a walk
+const MIN_WALK_FLIPS: u64 = 10_000;
+
+/// Seed for random phases
+const RANDOM_SEED: u64 = 0x9e37_79b9_7f4a_7c15;
+
+/// Ways to reset the saved phases
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Rephase {
+    /// every variable gets the original phase
+    Original,
+    /// every variable gets the negated original phase
+    Inverted,
+    /// the values of the longest conflict-free trail since the last rephase
+    Best,
+    /// random values
+    Random,
+    /// the best assignment found by local search starting from the saved phases
+    Walk,
+}
+
+/// Rephasing policies in the order they are applied, repeated after the last one
+const POLICIES: [Rephase; 9] = [
+    Rephase::Original,
+    Rephase::Best,
+    Rephase::Walk,
+    Rephase::Inverted,
+    Rephase::Best,
+    Rephase::Walk,
+    Rephase::Random,
+    Rephase::Best,
+    Rephase::Walk,
+];
+
+/// Schedules rephasing at arithmetically growing conflict intervals, rotating the policies
+#[derive(Debug)]
+pub struct RephaseSchedule {
+    /// number of conflicts at which the next rephase is due
+    next_rephase: u64,
+    count: usize,
+    /// ticks at the end of the last walk
+    last_walk_ticks: u64,
+    rng: XorShift,
+}
+
+impl Default for RephaseSchedule {
+    fn default() -> Self {
+        RephaseSchedule {
+            next_rephase: REPHASE_INTERVAL,
+            count: 0,
+            last_walk_ticks: 0,
+            rng: XorShift::new(RANDOM_SEED),
+        }
+    }
+}
+
+impl RephaseSchedule {
+    pub fn is_due(&self, conflicts: u64) -> bool {
+        conflicts >= self.next_rephase
+    }
+
+    /// Resets the saved phases with the next policy and schedules the next rephase. The context
+    /// has to be on the top decision level.
+    pub fn rephase(&mut self, bcp: &mut BcpContext, conflicts: u64) -> Rephase {
+        let policy = POLICIES[self.count % POLICIES.len()];
+        self.count += 1;
+        self.next_rephase = conflicts + REPHASE_INTERVAL * (self.count as u64 + 1);
+
+        let phases = &mut bcp.phases;
+        match policy {
+            Rephase::Original => phases.saved.fill(ORIGINAL_PHASE),
+            Rephase::Inverted => phases.saved.fill(!ORIGINAL_PHASE),
+            Rephase::Best => phases.saved.copy_from_slice(&phases.best),
+            Rephase::Random => {
+                let rng = &mut self.rng;
+                phases
+                    .saved
+                    .iter_mut()
+                    .for_each(|p| *p = rng.next() & 1 == 1);
+            }
+            Rephase::Walk => self.walk(bcp),
+        }
+
+        // the new phases are also the target until a longer conflict-free trail is found
+        let phases = &mut bcp.phases;
+        phases.target.copy_from_slice(&phases.saved);
+        phases.reset_target();
+        phases.reset_best();
+        policy
+    }
+
+    /// Replaces the saved phases by the best assignment found by local search on the irredundant
+    /// clauses, starting from the saved phases
+    fn walk(&mut self, bcp: &mut BcpContext) {
+        let flips =
+            MIN_WALK_FLIPS.max((bcp.ticks - self.last_walk_ticks) * WALK_EFFORT_PERCENT / 100);
+        self.last_walk_ticks = bcp.ticks;
+
+        let clauses = bcp.irredundant_clauses();
+        let mut local_search = LocalSearch::from_clauses(bcp.assignment.variable_count(), &clauses)
+            .with_seed(self.rng.next());
+        bcp.phases.saved = local_search.walk(&bcp.phases.saved, flips);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_rotate_policies() {
+        let cnf = CNF::from_dimacs("1 2 0\n-1 -2 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut schedule = RephaseSchedule::default();
+
+        assert!(!schedule.is_due(REPHASE_INTERVAL - 1));
+        assert!(schedule.is_due(REPHASE_INTERVAL));
+
+        let policies = (0..POLICIES.len() + 1)
+            .map(|_| schedule.rephase(&mut bcp, 0))
+            .collect::<Vec<_>>();
+        assert_eq!(&policies[..POLICIES.len()], POLICIES);
+        assert_eq!(policies[POLICIES.len()], Rephase::Original);
+    }
+
+    #[test]
+    fn test_walk_phases() {
+        // the original phases falsify (-1 v -2)
+        let cnf = CNF::from_dimacs("1 2 0\n-1 -2 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut schedule = RephaseSchedule::default();
+
+        schedule.walk(&mut bcp);
+
+        assert_ne!(bcp.phases.saved[0], bcp.phases.saved[1]);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::search::policy::{ConflictInfo, RestartPolicy};
+
+/// Smoothing factor of the moving average of recent glues
+const FAST_ALPHA: f64 = 1.0 / 32.0;
+
+/// Smoothing factor of the moving average of all glues
+const SLOW_ALPHA: f64 = 1.0 / 100_000.0;
+
+/// Glue restarts happen once the recent glues exceed the long term average by this factor
+const GLUE_MARGIN: f64 = 1.1;
+
+/// Minimum number of conflicts between two glue restarts
+const MIN_GLUE_INTERVAL: u64 = 2;
+
+/// Conflicts per unit of the Luby sequence
+const LUBY_BASE: u64 = 1_024;
+
+/// When the search restarts from the top decision level
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Restarts {
+    /// whenever recently learned clauses have a higher glue than usual (Audemard and Simon 2012)
+    Glue,
+    /// after conflict intervals following the Luby sequence
+    Luby,
+    /// never
+    Never,
+}
+
+/// Exponential moving average, which averages over the samples seen so far until there are
+/// enough of them for the smoothing factor
+#[derive(Default, Debug)]
+struct MovingAverage {
+    value: f64,
+    samples: u64,
+}
+
+impl MovingAverage {
+    fn update(&mut self, sample: f64, alpha: f64) {
+        self.samples += 1;
+        let alpha = alpha.max(1.0 / self.samples as f64);
+        self.value += alpha * (sample - self.value);
+    }
+}
+
+/// The built-in restart policies, decides after every conflict whether the search should restart
+#[derive(Debug)]
+pub struct RestartSchedule {
+    policy: Restarts,
+    fast_glue: MovingAverage,
+    slow_glue: MovingAverage,
+    conflicts_since_restart: u64,
+    /// restarts scheduled by the Luby sequence so far
+    luby_count: u64,
+}
+
+impl RestartSchedule {
+    pub fn new(policy: Restarts) -> RestartSchedule {
+        RestartSchedule {
+            policy,
+            fast_glue: Default::default(),
+            slow_glue: Default::default(),
+            conflicts_since_restart: 0,
+            luby_count: 0,
+        }
+    }
+}
+
+impl RestartPolicy for RestartSchedule {
+    /// Records the glue of the clause learned from a conflict
+    fn conflict(&mut self, conflict: &ConflictInfo) {
+        self.conflicts_since_restart += 1;
+        let glue = conflict.glue as f64;
+        self.fast_glue.update(glue, FAST_ALPHA);
+        self.slow_glue.update(glue, SLOW_ALPHA);
+    }
+
+    fn should_restart(&mut self) -> bool {
+        match self.policy {
+            Restarts::Glue => {
+                self.conflicts_since_restart >= MIN_GLUE_INTERVAL
+                    && self.fast_glue.value > GLUE_MARGIN * self.slow_glue.value
+            }
+            Restarts::Luby => self.conflicts_since_restart >= LUBY_BASE * luby(self.luby_count + 1),
+            Restarts::Never => false,
+        }
+    }
+
+    fn restarted(&mut self) {
+        self.conflicts_since_restart = 0;
+        if self.policy == Restarts::Luby {
+            self.luby_count += 1;
+        }
+    }
+}
+
+/// Returns the i-th element of the Luby sequence 1, 1, 2, 1, 1, 2, 4, 1, ... starting at 1
+fn luby(i: u64) -> u64 {
+    let mut i = i;
+    loop {
+        // find the smallest complete subsequence 2^k - 1 containing i
+        let mut size = 1;
+        while size < i {
+            size = 2 * size + 1;
+        }
+        if size == i {
+            return size.div_ceil(2);
+        }
+        i -= size / 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luby_sequence() {
+        let sequence = (1..=15).map(luby).collect::<Vec<_>>();
+        assert_eq!(sequence, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_glue_restarts() {
+        let mut schedule = RestartSchedule::new(Restarts::Glue);
+        let conflict = |glue| ConflictInfo {
+            glue,
+            trail_length: 100,
+            conflicts: 0,
+        };
+        for _ in 0..1000 {
+            schedule.conflict(&conflict(5));
+        }
+        assert!(!schedule.should_restart());
+
+        for _ in 0..10 {
+            schedule.conflict(&conflict(20));
+        }
+        assert!(schedule.should_restart());
+
+        schedule.restarted();
+        assert!(!schedule.should_restart());
+    }
+}
@@ -0,0 +1,79 @@
+/// Conflicts before the first inprocessing round
+const FIRST_INTERVAL: u64 = 2_000;
+
+/// The conflict interval between rounds grows by this percentage after every round
+const INTERVAL_GROWTH_PERCENT: u64 = 50;
+
+/// Every inprocessing pass may use this percentage of the search ticks since the last round
+const EFFORT_PERCENT: u64 = 10;
+
+/// Minimum effort of every inprocessing pass
+const MIN_EFFORT: u64 = 10_000;
+
+/// Schedules inprocessing rounds at geometrically growing conflict intervals
+#[derive(Debug)]
+pub struct InprocessingSchedule {
+    /// number of conflicts at which the next round is due
+    next_round: u64,
+    interval: u64,
+    /// ticks at the end of the last round
+    last_ticks: u64,
+}
+
+impl Default for InprocessingSchedule {
+    fn default() -> Self {
+        InprocessingSchedule {
+            next_round: FIRST_INTERVAL,
+            interval: FIRST_INTERVAL,
+            last_ticks: 0,
+        }
+    }
+}
+
+impl InprocessingSchedule {
+    pub fn is_due(&self, conflicts: u64) -> bool {
+        conflicts >= self.next_round
+    }
+
+    /// Returns the effort for every pass of a round that starts now
+    pub fn effort(&self, ticks: u64) -> u64 {
+        MIN_EFFORT.max((ticks - self.last_ticks) * EFFORT_PERCENT / 100)
+    }
+
+    /// Schedules the next round after a round finished, the ticks used by the round itself do
+    /// not count towards the effort of the next round
+    pub fn finish_round(&mut self, conflicts: u64, ticks: u64) {
+        self.next_round = conflicts + self.interval;
+        self.interval += self.interval * INTERVAL_GROWTH_PERCENT / 100;
+        self.last_ticks = ticks;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_growing_intervals() {
+        let mut schedule = InprocessingSchedule::default();
+        assert!(!schedule.is_due(FIRST_INTERVAL - 1));
+        assert!(schedule.is_due(FIRST_INTERVAL));
+
+        schedule.finish_round(FIRST_INTERVAL, 0);
+        assert!(!schedule.is_due(2 * FIRST_INTERVAL - 1));
+        assert!(schedule.is_due(2 * FIRST_INTERVAL));
+
+        schedule.finish_round(2 * FIRST_INTERVAL, 0);
+        assert!(schedule.is_due(2 * FIRST_INTERVAL + 3 * FIRST_INTERVAL / 2));
+    }
+
+    #[test]
+    fn test_effort_proportional_to_ticks() {
+        let mut schedule = InprocessingSchedule::default();
+        assert_eq!(schedule.effort(0), MIN_EFFORT);
+        assert_eq!(schedule.effort(1_000_000), 100_000);
+
+        schedule.finish_round(FIRST_INTERVAL, 1_000_000);
+        assert_eq!(schedule.effort(2_000_000), 100_000);
+    }
+}
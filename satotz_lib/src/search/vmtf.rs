@@ -0,0 +1,171 @@
+use crate::assignment::VariableAssignment;
+use crate::literal::Variable;
+use crate::resize::Resize;
+
+/// Marks the ends of the queue
+const NONE: u32 = u32::MAX;
+
+/// Variable move-to-front decision queue (Ryan 2004, Biere and Fröhlich 2015).
+///
+/// Variables are kept in a doubly linked list ordered by the time they were last bumped, bumped
+/// variables are moved to the end, from where decisions are taken. Every variable has a stamp
+/// that increases along the list. All variables after the search position are assigned, so
+/// decisions resume the scan there instead of at the end.
+#[derive(Default, Debug)]
+pub struct Vmtf {
+    previous: Vec<u32>,
+    next: Vec<u32>,
+    stamps: Vec<u64>,
+    /// least recently bumped variable
+    first: u32,
+    /// most recently bumped variable
+    last: u32,
+    /// all variables after this one are assigned
+    search: u32,
+    stamp: u64,
+}
+
+impl Resize for Vmtf {
+    fn resize(&mut self, var_count: usize) {
+        if self.stamps.is_empty() {
+            self.first = NONE;
+            self.last = NONE;
+        }
+
+        // new variables are enqueued as the most recently bumped ones
+        for index in self.stamps.len()..var_count {
+            self.previous.push(NONE);
+            self.next.push(NONE);
+            self.stamps.push(0);
+            self.enqueue(index as u32);
+        }
+        self.search = self.last;
+    }
+}
+
+impl Vmtf {
+    /// Returns the unassigned variable that was bumped most recently or `None` if all variables
+    /// are assigned
+    pub fn decide(&mut self, assignment: &VariableAssignment) -> Option<Variable> {
+        let mut index = self.search;
+        while index != NONE && !assignment.is_unknown(Variable::from_index(index)) {
+            index = self.previous[index as usize];
+        }
+
+        if index == NONE {
+            return None;
+        }
+        self.search = index;
+        Some(Variable::from_index(index))
+    }
+
+    /// Returns true if `a` would be decided before `b`
+    pub fn prefers(&self, a: Variable, b: Variable) -> bool {
+        self.stamps[a.as_index()] > self.stamps[b.as_index()]
+    }
+
+    /// Moves the variables to the end of the queue, keeping their relative order
+    pub fn bump(&mut self, variables: &mut [Variable], assignment: &VariableAssignment) {
+        variables.sort_unstable_by_key(|v| self.stamps[v.as_index()]);
+
+        for variable in variables.iter() {
+            let index = variable.index();
+            if index == self.last {
+                continue;
+            }
+            self.dequeue(index);
+            self.enqueue(index);
+            if assignment.is_unknown(*variable) {
+                self.search = index;
+            }
+        }
+    }
+
+    /// Moves the variable to the end of the queue as if it was bumped, the next decision scans
+    /// the whole queue again
+    pub fn raise(&mut self, variable: Variable) {
+        let index = variable.index();
+        if index != self.last {
+            self.dequeue(index);
+            self.enqueue(index);
+        }
+        self.search = self.last;
+    }
+
+    /// Makes sure that the search position is not before an unassigned variable
+    pub fn unassigned(&mut self, variable: Variable) {
+        if self.search == NONE
+            || self.stamps[variable.as_index()] > self.stamps[self.search as usize]
+        {
+            self.search = variable.index();
+        }
+    }
+
+    /// Scans the whole queue at the next decision
+    pub fn reset_search(&mut self) {
+        self.search = self.last;
+    }
+
+    fn enqueue(&mut self, index: u32) {
+        self.stamp += 1;
+        self.stamps[index as usize] = self.stamp;
+        self.previous[index as usize] = self.last;
+        self.next[index as usize] = NONE;
+        if self.last == NONE {
+            self.first = index;
+        } else {
+            self.next[self.last as usize] = index;
+        }
+        self.last = index;
+    }
+
+    fn dequeue(&mut self, index: u32) {
+        let previous = self.previous[index as usize];
+        let next = self.next[index as usize];
+
+        if previous == NONE {
+            self.first = next;
+        } else {
+            self.next[previous as usize] = next;
+        }
+        if next == NONE {
+            self.last = previous;
+        } else {
+            self.previous[next as usize] = previous;
+        }
+
+        if self.search == index {
+            self.search = if previous == NONE { next } else { previous };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::literal::Literal;
+
+    #[test]
+    fn test_decide_most_recently_bumped() {
+        let mut assignment = VariableAssignment::default();
+        assignment.resize(4);
+        let mut vmtf = Vmtf::default();
+        vmtf.resize(4);
+
+        assert_eq!(vmtf.decide(&assignment), Some(Variable::from_index(3)));
+
+        vmtf.bump(
+            &mut [Variable::from_index(1), Variable::from_index(0)],
+            &assignment,
+        );
+        assert_eq!(vmtf.decide(&assignment), Some(Variable::from_index(1)));
+
+        assignment.assign_true(Literal::from_index(1, true));
+        assignment.assign_true(Literal::from_index(0, false));
+        assert_eq!(vmtf.decide(&assignment), Some(Variable::from_index(3)));
+
+        assignment.assign_unknown(Variable::from_index(0));
+        vmtf.unassigned(Variable::from_index(0));
+        assert_eq!(vmtf.decide(&assignment), Some(Variable::from_index(0)));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::assignment::VariableAssignment;
+use crate::literal::Variable;
+use crate::resize::Resize;
+use crate::search::heap::VariableHeap;
+
+/// The bump increment grows by the inverse of this factor after every conflict, which decays
+/// all earlier bumps
+const DECAY: f64 = 0.95;
+
+/// Scores are rescaled once the increment exceeds this value
+const MAX_INCREMENT: f64 = 1e100;
+
+/// Variable state independent decaying sum decision heuristic (Moskewicz et al. 2001).
+///
+/// Variables involved in a conflict are bumped by an increment that grows exponentially with the
+/// number of conflicts, so recent conflicts dominate the scores. Unassigned variables are kept in
+/// a heap ordered by their scores, assigned variables are removed lazily at decisions.
+#[derive(Default, Debug)]
+pub struct Vsids {
+    scores: Vec<f64>,
+    increment: f64,
+    heap: VariableHeap,
+}
+
+impl Resize for Vsids {
+    fn resize(&mut self, var_count: usize) {
+        if self.scores.is_empty() {
+            self.increment = 1.0;
+        }
+
+        let old_count = self.scores.len();
+        self.scores.resize(var_count, 0.0);
+        self.heap.resize(var_count);
+        for index in old_count..var_count {
+            self.heap
+                .insert(Variable::from_index(index as u32), &self.scores);
+        }
+    }
+}
+
+impl Vsids {
+    /// Returns the unassigned variable with the highest score or `None` if all variables are
+    /// assigned
+    pub fn decide(&mut self, assignment: &VariableAssignment) -> Option<Variable> {
+        while let Some(variable) = self.heap.pop(&self.scores) {
+            if assignment.is_unknown(variable) {
+                return Some(variable);
+            }
+        }
+        None
+    }
+
+    /// Returns the unassigned variable with the highest score without removing it from the heap
+    pub fn next_decision(&mut self, assignment: &VariableAssignment) -> Option<Variable> {
+        let variable = self.decide(assignment)?;
+        self.heap.insert(variable, &self.scores);
+        Some(variable)
+    }
+
+    /// Returns true if `a` would be decided before `b`
+    pub fn prefers(&self, a: Variable, b: Variable) -> bool {
+        self.scores[a.as_index()] > self.scores[b.as_index()]
+    }
+
+    /// Increases the scores of the variables and decays the scores of all other variables
+    pub fn bump(&mut self, variables: &[Variable]) {
+        for variable in variables {
+            self.scores[variable.as_index()] += self.increment;
+            self.heap.increased(*variable, &self.scores);
+        }
+
+        self.increment /= DECAY;
+        if self.increment > MAX_INCREMENT {
+            // scaling all scores by the same factor keeps the order of the heap
+            self.scores.iter_mut().for_each(|s| *s /= MAX_INCREMENT);
+            self.increment /= MAX_INCREMENT;
+        }
+    }
+
+    /// Increases the score of the variable as if it was bumped in that many conflicts, decreases
+    /// it for negative counts
+    pub fn raise(&mut self, variable: Variable, conflicts: f64) {
+        self.scores[variable.as_index()] += conflicts * self.increment;
+        if conflicts > 0.0 {
+            self.heap.increased(variable, &self.scores);
+        } else {
+            self.heap.decreased(variable, &self.scores);
+        }
+    }
+
+    /// Makes an unassigned variable available for decisions again
+    pub fn unassigned(&mut self, variable: Variable) {
+        self.heap.insert(variable, &self.scores);
+    }
+
+    /// Rebuilds the heap from all unassigned variables
+    pub fn reset_search(&mut self, assignment: &VariableAssignment) {
+        self.heap.clear();
+        for variable in assignment.unassigned() {
+            self.heap.insert(variable, &self.scores);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::literal::Literal;
+
+    #[test]
+    fn test_decide_highest_score() {
+        let mut assignment = VariableAssignment::default();
+        assignment.resize(3);
+        let mut vsids = Vsids::default();
+        vsids.resize(3);
+
+        vsids.bump(&[Variable::from_index(1), Variable::from_index(2)]);
+        vsids.bump(&[Variable::from_index(2)]);
+        assert_eq!(vsids.decide(&assignment), Some(Variable::from_index(2)));
+
+        assignment.assign_true(Literal::from_index(2, true));
+        assert_eq!(vsids.decide(&assignment), Some(Variable::from_index(1)));
+
+        assignment.assign_unknown(Variable::from_index(2));
+        vsids.unassigned(Variable::from_index(2));
+        assert_eq!(vsids.decide(&assignment), Some(Variable::from_index(2)));
+    }
+
+    #[test]
+    fn test_next_decision_stays_in_heap() {
+        let mut assignment = VariableAssignment::default();
+        assignment.resize(2);
+        let mut vsids = Vsids::default();
+        vsids.resize(2);
+
+        vsids.bump(&[Variable::from_index(1)]);
+        assert!(vsids.prefers(Variable::from_index(1), Variable::from_index(0)));
+        assert_eq!(
+            vsids.next_decision(&assignment),
+            Some(Variable::from_index(1))
+        );
+        assert_eq!(vsids.decide(&assignment), Some(Variable::from_index(1)));
+    }
+}
@@ -0,0 +1,136 @@
+use crate::bcp::{propagate, trail, BcpContext};
+use crate::reconstruct::ReconstructionStack;
+use crate::statistics::Statistics;
+
+mod bva;
+mod elim;
+mod occurrences;
+mod probe;
+mod subsume;
+mod ternary;
+pub mod top_level;
+mod transitive;
+mod vivify;
+
+/// Maximum number of propagation ticks spent on failed literal probing
+const PROBE_EFFORT: u64 = 10_000_000;
+
+/// Maximum number of binary implications visited during transitive reduction
+const TRANSITIVE_REDUCTION_EFFORT: usize = 1_000_000;
+
+/// Maximum number of literals visited during resolution in bounded variable elimination
+const ELIMINATION_EFFORT: usize = 10_000_000;
+
+/// Maximum number of literals visited during subsumption
+const SUBSUMPTION_EFFORT: usize = 10_000_000;
+
+/// Maximum number of literals visited while matching clauses for bounded variable addition
+const BVA_EFFORT: usize = 10_000_000;
+
+/// Vivification may use this percentage of the ticks spent in propagation so far
+const VIVIFY_EFFORT_PERCENT: u64 = 10;
+
+/// Minimum number of ticks for vivification, so that it also runs before the search
+const VIVIFY_MIN_EFFORT: u64 = 100_000;
+
+/// Runs the simplification passes on the formula at the top decision level.
+///
+/// Clauses removed by variable elimination are pushed to the reconstruction stack, variables with a
+/// non-zero count in `frozen` are not eliminated. Bounded variable addition introduces new
+/// variables, the caller has to resize its datastructures to `bcp.assignment.variable_count()`
+/// afterwards.
+pub fn simplify(bcp: &mut BcpContext, reconstruction: &mut ReconstructionStack, frozen: &[u32]) {
+    if bcp.trail.current_decision_level() != trail::TOP_DECISION_LEVEL {
+        trail::backtrack(bcp, trail::TOP_DECISION_LEVEL);
+    }
+
+    if bcp.is_unsat || propagate(bcp).is_err() {
+        bcp.set_unsat();
+        return;
+    }
+
+    top_level::simplify_top_level(bcp);
+    probe::probe(bcp, PROBE_EFFORT);
+    transitive::transitive_reduction(bcp, TRANSITIVE_REDUCTION_EFFORT);
+
+    // subsumption and elimination need occurrence lists without units found so far
+    if !simplify_new_units(bcp) {
+        return;
+    }
+    subsume::subsume(bcp, SUBSUMPTION_EFFORT);
+
+    if !simplify_new_units(bcp) {
+        return;
+    }
+    elim::eliminate_variables(bcp, reconstruction, frozen, ELIMINATION_EFFORT);
+    bva::bounded_variable_addition(bcp, BVA_EFFORT);
+
+    let vivify_effort = VIVIFY_MIN_EFFORT.max(bcp.ticks * VIVIFY_EFFORT_PERCENT / 100);
+    vivify::vivify(bcp, vivify_effort);
+
+    if !bcp.is_unsat {
+        top_level::simplify_top_level(bcp);
+    }
+}
+
+/// Runs one round of inprocessing on the top decision level during the search.
+///
+/// Probing, vivification, ternary resolution, subsumption and variable elimination each get
+/// `effort` ticks, visited literals or resolved clauses. The gains of every pass are added to the
+/// statistics.
+pub fn inprocess(
+    bcp: &mut BcpContext,
+    reconstruction: &mut ReconstructionStack,
+    frozen: &[u32],
+    effort: u64,
+    stats: &mut Statistics,
+) {
+    assert_eq!(
+        bcp.trail.current_decision_level(),
+        trail::TOP_DECISION_LEVEL
+    );
+
+    if bcp.is_unsat || propagate(bcp).is_err() {
+        bcp.set_unsat();
+        return;
+    }
+    top_level::simplify_top_level(bcp);
+
+    let failed_literals = probe::probe(bcp, effort);
+    stats.failed_literals += failed_literals as u64;
+
+    let vivified_clauses = vivify::vivify(bcp, effort);
+    stats.vivified_clauses += vivified_clauses as u64;
+
+    // ternary resolution, subsumption and elimination need clauses without units found so far
+    if !simplify_new_units(bcp) {
+        return;
+    }
+    let ternary_resolvents = ternary::ternary_resolution(bcp, effort as usize);
+    stats.ternary_resolvents += ternary_resolvents as u64;
+
+    let subsumed_clauses = subsume::subsume(bcp, effort as usize);
+    stats.subsumed_clauses += subsumed_clauses as u64;
+
+    if !simplify_new_units(bcp) {
+        return;
+    }
+
+    let eliminated_variables =
+        elim::eliminate_variables(bcp, reconstruction, frozen, effort as usize);
+    stats.eliminated_variables += eliminated_variables as u64;
+
+    if !bcp.is_unsat {
+        top_level::simplify_top_level(bcp);
+    }
+}
+
+/// Removes satisfied clauses and false literals on the top level, returns false if the formula is
+/// unsatisfiable
+fn simplify_new_units(bcp: &mut BcpContext) -> bool {
+    if bcp.is_unsat {
+        return false;
+    }
+    top_level::simplify_top_level(bcp);
+    true
+}
@@ -0,0 +1,209 @@
+use crate::bcp::BcpContext;
+use crate::literal::Literal;
+use crate::resize::Resize;
+use crate::simplify::occurrences::Occurrences;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Number of clauses saved by replacing `literals * clauses` clauses with `literals + clauses`
+fn reduction(literals: usize, clauses: usize) -> isize {
+    (literals * clauses) as isize - (literals + clauses) as isize
+}
+
+/// Simple bounded variable addition (Manthey, Heule and Biere 2012).
+///
+/// Finds a set of literals `L` and a set of clauses `C`, all containing the literal `l`, such that
+/// for every `l'` in `L` and every clause `c` in `C` the clause `c` with `l` replaced by `l'` is
+/// in the formula. These `|L| * |C|` clauses are replaced by the `|L| + |C|` clauses
+/// `(l' v -x)` and `(c \ l v x)` over a fresh variable `x`.
+///
+/// The search is bounded by `effort`, the number of visited literals. New variables are added to
+/// the context, so the caller has to resize its datastructures afterwards.
+pub fn bounded_variable_addition(bcp: &mut BcpContext, mut effort: usize) {
+    let mut occurrences = Occurrences::from_bcp(bcp);
+    let mut marks = vec![false; occurrences.counts.len()];
+
+    let mut queue = occurrences
+        .counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(code, count)| (*count, Literal::from_code(code)))
+        .collect::<BinaryHeap<_>>();
+
+    while let Some((count, literal)) = queue.pop() {
+        if count != occurrences.counts[literal.as_index()] {
+            // stale entry
+            continue;
+        }
+        if effort == 0 {
+            break;
+        }
+
+        let mut matched_literals = vec![literal];
+        let mut matched_clauses = occurrences.clauses_with(literal);
+        // maps a matched clause and a replacement literal to the clause with the replacement
+        let mut replaced: HashMap<(usize, Literal), usize> = HashMap::new();
+
+        loop {
+            let candidates = replacement_candidates(
+                &occurrences,
+                literal,
+                &matched_literals,
+                &matched_clauses,
+                &mut marks,
+                &mut replaced,
+                &mut effort,
+            );
+
+            let best = candidates
+                .into_iter()
+                .max_by_key(|(l, clauses)| (clauses.len(), std::cmp::Reverse(*l)));
+
+            match best {
+                Some((best_literal, clauses))
+                    if reduction(matched_literals.len() + 1, clauses.len())
+                        > reduction(matched_literals.len(), matched_clauses.len()) =>
+                {
+                    matched_literals.push(best_literal);
+                    matched_clauses = clauses;
+                }
+                _ => break,
+            }
+        }
+
+        if reduction(matched_literals.len(), matched_clauses.len()) <= 0 {
+            continue;
+        }
+
+        // fresh variable x
+        let var_count = bcp.assignment.variable_count();
+        bcp.resize(var_count + 1);
+        occurrences.resize(var_count + 1);
+        marks.resize(occurrences.counts.len(), false);
+        let x = Literal::from_index(var_count as u32, true);
+
+        // (l' v -x) is RAT on -x and (c \ l v x) is RAT on x, the pivot has to come first
+        for &l in &matched_literals {
+            occurrences.add_clause(bcp, vec![!x, l]);
+        }
+        for &c in &matched_clauses {
+            let mut literals = vec![x];
+            literals.extend(occurrences.literals(c).iter().filter(|l| **l != literal));
+            occurrences.add_clause(bcp, literals);
+        }
+
+        for &c in &matched_clauses {
+            for &l in &matched_literals {
+                let clause = if l == literal { c } else { replaced[&(c, l)] };
+                occurrences.remove_clause(bcp, clause);
+            }
+        }
+
+        queue.push((occurrences.counts[literal.as_index()], literal));
+    }
+}
+
+/// Finds the clauses `c \ l v l'` for all matched clauses `c`, grouped by the literal `l'`
+fn replacement_candidates(
+    occurrences: &Occurrences,
+    literal: Literal,
+    matched_literals: &[Literal],
+    matched_clauses: &[usize],
+    marks: &mut [bool],
+    replaced: &mut HashMap<(usize, Literal), usize>,
+    effort: &mut usize,
+) -> HashMap<Literal, Vec<usize>> {
+    let mut candidates: HashMap<Literal, Vec<usize>> = HashMap::new();
+    let mut used: HashSet<(usize, Literal)> = HashSet::new();
+
+    for &c in matched_clauses {
+        let mut replacements: HashSet<Literal> = HashSet::new();
+        let literals = occurrences.literals(c);
+        let rest = literals
+            .iter()
+            .copied()
+            .filter(|l| *l != literal)
+            .collect::<Vec<_>>();
+
+        // the least occurring literal of the remaining literals has the shortest list to scan
+        let Some(&min_literal) = rest.iter().min_by_key(|l| occurrences.counts[l.as_index()])
+        else {
+            continue;
+        };
+
+        rest.iter().for_each(|l| marks[l.as_index()] = true);
+
+        for d in occurrences.clauses_with(min_literal) {
+            let other = occurrences.literals(d);
+            *effort = effort.saturating_sub(other.len());
+
+            if d == c || other.len() != literals.len() {
+                continue;
+            }
+
+            let mut unmarked = other.iter().filter(|l| !marks[l.as_index()]);
+            let (Some(&l), None) = (unmarked.next(), unmarked.next()) else {
+                continue;
+            };
+
+            if l == literal || l == !literal || matched_literals.contains(&l) {
+                continue;
+            }
+            // every clause may only replace one matched clause
+            if !replacements.insert(l) || !used.insert((d, l)) {
+                continue;
+            }
+
+            replaced.insert((c, l), d);
+            candidates.entry(l).or_default().push(c);
+        }
+
+        rest.iter().for_each(|l| marks[l.as_index()] = false);
+    }
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_replace_clause_product() {
+        // (1 v 2 v 3) x (4 v 5 v 6): 9 clauses are replaced by 6 over a fresh variable 7
+        let cnf =
+            CNF::from_dimacs("1 4 0\n1 5 0\n1 6 0\n2 4 0\n2 5 0\n2 6 0\n3 4 0\n3 5 0\n3 6 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        bounded_variable_addition(&mut bcp, 10_000);
+
+        assert_eq!(bcp.assignment.variable_count(), 7);
+
+        let occurrences = Occurrences::from_bcp(&mut bcp);
+        let mut clauses = occurrences
+            .clauses
+            .into_iter()
+            .flatten()
+            .map(|(literals, _)| literals.iter().map(|l| l.as_dimacs_integer()).collect())
+            .collect::<Vec<Vec<i32>>>();
+        clauses.sort_unstable();
+
+        assert_eq!(clauses.len(), 6);
+        for other in [1, 2, 3, 4, 5, 6] {
+            assert!(clauses
+                .iter()
+                .any(|c| c.contains(&other) && c.iter().any(|l| l.abs() == 7)));
+        }
+    }
+
+    #[test]
+    fn test_no_reduction() {
+        let cnf = CNF::from_dimacs("1 4 0\n1 5 0\n2 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        bounded_variable_addition(&mut bcp, 10_000);
+
+        assert_eq!(bcp.assignment.variable_count(), 5);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::bcp::{propagate, BcpContext};
+use crate::literal::{Literal, Variable};
+use crate::reconstruct::ReconstructionStack;
+use crate::simplify::occurrences::Occurrences;
+
+/// Variables with more occurrences of one polarity are not eliminated
+const OCCURRENCE_LIMIT: usize = 16;
+
+/// Variables whose elimination produces longer resolvents are not eliminated
+const RESOLVENT_LIMIT: usize = 16;
+
+/// Bounded variable elimination (Een and Biere 2005).
+///
+/// A variable `x` is eliminated by replacing all irredundant clauses containing `x` or `-x` by
+/// their non-tautological resolvents on `x`, if this does not increase the number of clauses.
+/// The removed clauses are pushed on the reconstruction stack with `x` or `-x` as witness, and
+/// learned long clauses with eliminated variables are deleted.
+///
+/// Frozen variables, with a non-zero count in `frozen`, are kept. Variables are tried in the order
+/// of the number of resolvents they could produce, until `effort`, the number of literals visited
+/// during resolution, is used up. Returns the number of eliminated variables.
+pub fn eliminate_variables(
+    bcp: &mut BcpContext,
+    reconstruction: &mut ReconstructionStack,
+    frozen: &[u32],
+    mut effort: usize,
+) -> usize {
+    let mut occurrences = Occurrences::from_bcp(bcp);
+    let mut eliminated = vec![false; bcp.assignment.variable_count()];
+
+    let mut candidates = (0..bcp.assignment.variable_count() as u32)
+        .map(Variable::from_index)
+        .collect::<Vec<_>>();
+    candidates.sort_by_key(|v| {
+        let positive = Literal::from_variable(v, true);
+        occurrences.counts[positive.as_index()] * occurrences.counts[(!positive).as_index()]
+    });
+
+    for variable in candidates {
+        if bcp.is_unsat || effort == 0 {
+            break;
+        }
+
+        let positive = Literal::from_variable(&variable, true);
+        if !bcp.assignment.literal_is_unknown(positive) || frozen[variable.as_index()] > 0 {
+            continue;
+        }
+
+        let positive_clauses = occurrences.clauses_with(positive);
+        let negative_clauses = occurrences.clauses_with(!positive);
+        let clause_count = positive_clauses.len() + negative_clauses.len();
+
+        if clause_count == 0
+            || positive_clauses.len() > OCCURRENCE_LIMIT
+            || negative_clauses.len() > OCCURRENCE_LIMIT
+        {
+            continue;
+        }
+
+        let mut resolvents = vec![];
+        let mut bounded = true;
+
+        'resolve: for &p in &positive_clauses {
+            for &n in &negative_clauses {
+                let p = occurrences.literals(p);
+                let n = occurrences.literals(n);
+                effort = effort.saturating_sub(p.len() + n.len());
+
+                if let Some(resolvent) = resolve(bcp, p, n, positive) {
+                    if resolvent.len() > RESOLVENT_LIMIT || resolvents.len() == clause_count {
+                        bounded = false;
+                        break 'resolve;
+                    }
+                    resolvents.push(resolvent);
+                }
+            }
+        }
+
+        if !bounded {
+            continue;
+        }
+
+        // resolvents are added before the antecedents are deleted from the proof
+        for mut resolvent in resolvents {
+            // unit resolvents may have assigned literals of the following resolvents
+            if resolvent.iter().any(|l| bcp.assignment.literal_is_true(*l)) {
+                continue;
+            }
+            resolvent.retain(|l| bcp.assignment.literal_is_unknown(*l));
+
+            let unit = resolvent.len() == 1;
+            occurrences.add_clause(bcp, resolvent);
+            if unit && propagate(bcp).is_err() {
+                bcp.set_unsat();
+            }
+        }
+
+        for (clauses, witness) in [(positive_clauses, positive), (negative_clauses, !positive)] {
+            for clause in clauses {
+                let literals = occurrences.remove_clause(bcp, clause);
+                reconstruction.push(witness, &literals);
+            }
+        }
+
+        eliminated[variable.as_index()] = true;
+    }
+
+    // learned clauses are implied, but should not reintroduce eliminated variables
+    for index in 0..bcp.long_clauses.clauses().len() {
+        let clause = &bcp.long_clauses.clauses()[index];
+        if clause.header().learned
+            && !clause.header().deleted
+            && clause
+                .literals()
+                .iter()
+                .any(|l| eliminated[l.variable().as_index()])
+        {
+            let literals = clause.literals().to_vec();
+            bcp.proof.delete_clause(&literals);
+            bcp.long_clauses.delete_clause(index);
+        }
+    }
+
+    eliminated.iter().filter(|e| **e).count()
+}
+
+/// Returns the resolvent of two clauses on the pivot, simplified by the top level assignment, or
+/// `None` if it is a tautology or satisfied
+fn resolve(
+    bcp: &BcpContext,
+    positive: &[Literal],
+    negative: &[Literal],
+    pivot: Literal,
+) -> Option<Vec<Literal>> {
+    let mut resolvent = positive
+        .iter()
+        .chain(negative.iter())
+        .copied()
+        .filter(|l| l.variable() != pivot.variable())
+        .collect::<Vec<_>>();
+
+    resolvent.sort_unstable();
+    resolvent.dedup();
+
+    let tautology = resolvent.windows(2).any(|w| w[0] == !w[1]);
+    if tautology || resolvent.iter().any(|l| bcp.assignment.literal_is_true(*l)) {
+        return None;
+    }
+
+    resolvent.retain(|l| bcp.assignment.literal_is_unknown(*l));
+    Some(resolvent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_eliminate_variable() {
+        // eliminating 2 from (-1 v 2) and (-2 v 3) gives (-1 v 3)
+        let cnf = CNF::from_dimacs("-1 2 0\n-2 3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut reconstruction = ReconstructionStack::new(3);
+
+        eliminate_variables(&mut bcp, &mut reconstruction, &[0; 3], 1000);
+
+        let occurrences = Occurrences::from_bcp(&mut bcp);
+        let clauses = occurrences.clauses.into_iter().flatten().count();
+        assert!(clauses <= 1);
+        assert!(!reconstruction.is_empty());
+
+        // every model of the remaining formula extends to a model of the original one
+        for values in [[1, -2, 3], [-1, 2, 3], [-1, -2, -3], [-1, 2, -3]] {
+            let mut model = values
+                .iter()
+                .map(|l| Literal::from_dimacs(*l))
+                .collect::<Vec<_>>();
+            reconstruction.extend(&mut model);
+            for clause in cnf.clauses() {
+                assert!(clause.literals().iter().any(|l| model.contains(l)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_effort_limit() {
+        let cnf = CNF::from_dimacs("-1 2 0\n-2 3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut reconstruction = ReconstructionStack::new(3);
+
+        eliminate_variables(&mut bcp, &mut reconstruction, &[0; 3], 0);
+
+        assert!(reconstruction.is_empty());
+        assert!(bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(-1), Literal::from_dimacs(2)]));
+    }
+
+    #[test]
+    fn test_frozen_variable() {
+        let cnf = CNF::from_dimacs("-1 2 0\n-2 3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        let mut reconstruction = ReconstructionStack::new(3);
+
+        eliminate_variables(&mut bcp, &mut reconstruction, &[1, 1, 1], 1000);
+
+        assert!(reconstruction.is_empty());
+    }
+
+    #[test]
+    fn test_resolve_tautology() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n");
+        let bcp = BcpContext::from_cnf(&cnf);
+        let lits = |c: &[i32]| {
+            c.iter()
+                .map(|l| Literal::from_dimacs(*l))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            resolve(
+                &bcp,
+                &lits(&[1, 2]),
+                &lits(&[-1, -2]),
+                Literal::from_dimacs(1)
+            ),
+            None
+        );
+        assert_eq!(
+            resolve(
+                &bcp,
+                &lits(&[1, 2]),
+                &lits(&[-1, 3]),
+                Literal::from_dimacs(1)
+            ),
+            Some(lits(&[2, 3]))
+        );
+    }
+}
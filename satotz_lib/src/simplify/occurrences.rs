@@ -0,0 +1,126 @@
+use crate::bcp::BcpContext;
+use crate::clause::ClauseIndex;
+use crate::literal::Literal;
+use crate::resize::Resize;
+
+/// Where a clause of the occurrence view is stored in the propagation datastructures
+#[derive(Debug, Copy, Clone)]
+pub enum StoredClause {
+    Binary([Literal; 2]),
+    Long(ClauseIndex),
+}
+
+/// Occurrence lists over all irredundant clauses without assigned literals
+#[derive(Default)]
+pub struct Occurrences {
+    /// sorted literals of every clause, `None` if the clause was removed
+    pub clauses: Vec<Option<(Vec<Literal>, StoredClause)>>,
+    /// maps a literal to the clauses containing it, removed clauses are skipped lazily
+    by_literal: Vec<Vec<usize>>,
+    /// maps a literal to its number of occurrences in not removed clauses
+    pub counts: Vec<usize>,
+}
+
+impl Resize for Occurrences {
+    fn resize(&mut self, var_count: usize) {
+        self.by_literal.resize(var_count * 2, vec![]);
+        self.counts.resize(var_count * 2, 0);
+    }
+}
+
+impl Occurrences {
+    /// Collects the binary clauses and the not learned long clauses, the context has to be on the
+    /// top decision level
+    pub fn from_bcp(bcp: &mut BcpContext) -> Occurrences {
+        let mut occurrences = Occurrences::default();
+        occurrences.resize(bcp.assignment.variable_count());
+
+        for code in 0..bcp.assignment.variable_count() * 2 {
+            let a = Literal::from_code(code);
+            let others = bcp.watch.binary_clauses(a).collect::<Vec<_>>();
+            for b in others {
+                if a < b {
+                    occurrences.add(bcp, vec![a, b], StoredClause::Binary([a, b]));
+                }
+            }
+        }
+
+        for (index, clause) in bcp.long_clauses.clauses().iter().enumerate() {
+            if !clause.header().deleted && !clause.header().learned {
+                let literals = clause.literals().to_vec();
+                occurrences.add(bcp, literals, StoredClause::Long(index));
+            }
+        }
+
+        occurrences
+    }
+
+    fn add(&mut self, bcp: &BcpContext, mut literals: Vec<Literal>, stored: StoredClause) {
+        if literals
+            .iter()
+            .any(|l| !bcp.assignment.literal_is_unknown(*l))
+        {
+            return;
+        }
+
+        literals.sort_unstable();
+        for literal in &literals {
+            self.by_literal[literal.as_index()].push(self.clauses.len());
+            self.counts[literal.as_index()] += 1;
+        }
+        self.clauses.push(Some((literals, stored)));
+    }
+
+    pub fn literals(&self, clause: usize) -> &[Literal] {
+        &self.clauses[clause].as_ref().unwrap().0
+    }
+
+    /// Returns the not removed clauses containing the literal
+    pub fn clauses_with(&self, literal: Literal) -> Vec<usize> {
+        self.by_literal[literal.as_index()]
+            .iter()
+            .copied()
+            .filter(|c| self.clauses[*c].is_some())
+            .collect()
+    }
+
+    /// Adds a derived clause to the formula and the occurrence lists.
+    ///
+    /// Units are assigned on the top level and have to be propagated by the caller.
+    pub fn add_clause(&mut self, bcp: &mut BcpContext, literals: Vec<Literal>) {
+        bcp.proof.add_clause(&literals);
+        let stored = match *literals {
+            [] => return bcp.set_unsat(),
+            [_] => {
+                bcp.add_clause(&literals);
+                return;
+            }
+            [a, b] => {
+                bcp.watch.add_binary_clause([a, b]);
+                StoredClause::Binary([a, b])
+            }
+            [_, _, ..] => {
+                let index = bcp.long_clauses.add_clause(&literals);
+                bcp.watch.watch_clause(index, &literals);
+                StoredClause::Long(index)
+            }
+        };
+        self.add(bcp, literals, stored);
+    }
+
+    /// Removes a clause from the formula and the occurrence lists, returning its literals
+    pub fn remove_clause(&mut self, bcp: &mut BcpContext, clause: usize) -> Vec<Literal> {
+        let (literals, stored) = self.clauses[clause].take().expect("clause already removed");
+        for literal in &literals {
+            self.counts[literal.as_index()] -= 1;
+        }
+
+        bcp.proof.delete_clause(&literals);
+        match stored {
+            StoredClause::Binary(literals) => bcp.watch.remove_binary_clause(literals),
+            StoredClause::Long(index) => bcp.long_clauses.delete_clause(index),
+        }
+
+        literals
+    }
+}
@@ -0,0 +1,185 @@
+use crate::bcp::trail::{Reason, TOP_DECISION_LEVEL};
+use crate::bcp::{propagate, trail, BcpContext};
+use crate::literal::Literal;
+use std::collections::HashMap;
+
+/// Failed literal probing on the top decision level.
+///
+/// Every unassigned literal that implies something over a binary clause is decided on level 1
+/// and propagated:
+///  - if propagation leads to a conflict, the literal is failed and its negation becomes a unit
+///  - otherwise every long clause that propagated on level 1 is turned into a hyper-binary
+///    resolvent (-d v l), where l is the propagated literal and d the dominator of the literals
+///    that falsified the clause in the binary implication tree rooted at the probe
+///
+/// Probing stops as soon as propagation used more than `effort` ticks. Returns the number of
+/// failed literals.
+pub fn probe(bcp: &mut BcpContext, effort: u64) -> usize {
+    let tick_limit = bcp.ticks.saturating_add(effort);
+    let mut failed = 0;
+
+    for var_index in 0..bcp.assignment.variable_count() as u32 {
+        for positive in [true, false] {
+            if bcp.is_unsat || bcp.ticks > tick_limit {
+                return failed;
+            }
+
+            let probe = Literal::from_index(var_index, positive);
+
+            if bcp.assignment.literal_is_unknown(probe)
+                && bcp.watch.binary_clauses(!probe).next().is_some()
+                && probe_literal(bcp, probe)
+            {
+                failed += 1;
+            }
+        }
+    }
+
+    failed
+}
+
+/// Probes a single literal, the context has to be fully propagated on the top decision level.
+/// Returns whether the literal failed.
+fn probe_literal(bcp: &mut BcpContext, probe: Literal) -> bool {
+    trail::decide_and_assign(bcp, probe);
+
+    match propagate(bcp) {
+        Err(_) => {
+            // failed literal
+            trail::backtrack_without_phases(bcp, TOP_DECISION_LEVEL);
+            bcp.learn_clause(&[!probe]);
+            if propagate(bcp).is_err() {
+                bcp.set_unsat();
+            }
+            true
+        }
+        Ok(_) => {
+            let resolvents = hyper_binary_resolvents(bcp);
+            trail::backtrack_without_phases(bcp, TOP_DECISION_LEVEL);
+            for resolvent in resolvents {
+                bcp.learn_clause(&resolvent);
+            }
+            false
+        }
+    }
+}
+
+/// Computes the hyper-binary resolvents for all long clauses that propagated on decision level 1
+fn hyper_binary_resolvents(bcp: &BcpContext) -> Vec<[Literal; 2]> {
+    let mut resolvents = vec![];
+
+    // maps a literal assigned on level 1 to the true literal that implied it over a binary clause
+    let mut implied_by: HashMap<Literal, Literal> = HashMap::new();
+
+    for step in bcp.trail.steps() {
+        if step.decision_level != 1 {
+            continue;
+        }
+
+        match step.reason {
+            Reason::Binary(false_literal) => {
+                implied_by.insert(step.assigned_literal, !false_literal);
+            }
+            Reason::Long(_) => {
+                let implying_literals = step
+                    .reason
+                    .causing_literals(bcp)
+                    .iter()
+                    .filter(|l| {
+                        bcp.trail.get_step_for_variable(l.variable()).decision_level
+                            != TOP_DECISION_LEVEL
+                    })
+                    .map(|l| !*l)
+                    .collect::<Vec<_>>();
+
+                let dominator = dominator(&implied_by, &implying_literals);
+                let resolvent = [!dominator, step.assigned_literal];
+
+                // the propagation is now explained by the binary implication tree
+                implied_by.insert(step.assigned_literal, dominator);
+
+                if !bcp.watch.contains_binary_clause(resolvent) {
+                    resolvents.push(resolvent);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    resolvents
+}
+
+/// Returns the deepest common ancestor of the given literals in the binary implication tree.
+///
+/// Using the dominator instead of the probe keeps the implication graph free of transitive edges:
+/// the probe already reaches the dominator, so an edge from the probe would be redundant.
+fn dominator(implied_by: &HashMap<Literal, Literal>, literals: &[Literal]) -> Literal {
+    // the path from the first literal up to the probe, all common ancestors are a suffix of it
+    let mut path = vec![literals[0]];
+    while let Some(&parent) = implied_by.get(path.last().unwrap()) {
+        path.push(parent);
+    }
+
+    for &literal in &literals[1..] {
+        let mut ancestor = literal;
+        loop {
+            if let Some(position) = path.iter().position(|&l| l == ancestor) {
+                path.drain(..position);
+                break;
+            }
+            ancestor = implied_by[&ancestor];
+        }
+    }
+
+    path[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_failed_literal() {
+        let cnf = CNF::from_dimacs("-1 2 0\n-1 3 0\n-2 -3 0\n1 4 5 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        assert_eq!(probe(&mut bcp, u64::MAX), 1);
+
+        assert!(bcp.assignment.literal_is_true(Literal::from_dimacs(-1)));
+        assert_eq!(
+            bcp.trail
+                .get_step_for_variable(Literal::from_dimacs(1).variable())
+                .reason,
+            Reason::Unit
+        );
+    }
+
+    #[test]
+    fn test_hyper_binary_resolvent() {
+        let cnf = CNF::from_dimacs("-1 2 0\n-1 3 0\n-2 -3 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        probe(&mut bcp, u64::MAX);
+
+        assert!(bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(-1), Literal::from_dimacs(4)]));
+    }
+
+    #[test]
+    fn test_resolvent_uses_dominator() {
+        // 1 -> 2, 2 -> 3, 2 -> 4, (3 & 4) -> 5: the dominator of 3 and 4 is 2
+        let cnf = CNF::from_dimacs("-1 2 0\n-2 3 0\n-2 4 0\n-3 -4 5 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        probe(&mut bcp, u64::MAX);
+
+        assert!(bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(-2), Literal::from_dimacs(5)]));
+        assert!(!bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(-1), Literal::from_dimacs(5)]));
+    }
+}
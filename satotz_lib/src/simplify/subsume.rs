@@ -0,0 +1,135 @@
+use crate::bcp::{propagate, BcpContext};
+use crate::literal::Literal;
+use crate::simplify::occurrences::Occurrences;
+
+/// Backward subsumption and self-subsuming resolution on the irredundant clauses.
+///
+/// Clauses are visited from short to long. A clause `c` removes every clause `d` with `c ⊆ d`,
+/// and strengthens every clause `d` that contains all literals of `c` except one, which occurs
+/// negated in `d`, by removing that negated literal from `d`.
+///
+/// Candidates for `d` are taken from the occurrence lists of the variable of `c` with the fewest
+/// occurrences. The pass stops once `effort`, the number of visited literals, is used up. Returns
+/// the number of removed or strengthened clauses.
+pub fn subsume(bcp: &mut BcpContext, mut effort: usize) -> usize {
+    let mut occurrences = Occurrences::from_bcp(bcp);
+    let mut marks = vec![false; occurrences.counts.len()];
+    let mut subsumed = 0;
+
+    let mut order = (0..occurrences.clauses.len()).collect::<Vec<_>>();
+    order.sort_by_key(|c| occurrences.literals(*c).len());
+
+    for c in order {
+        if bcp.is_unsat || effort == 0 {
+            break;
+        }
+        if occurrences.clauses[c].is_none() {
+            continue;
+        }
+
+        let literals = occurrences.literals(c).to_vec();
+        let &min_literal = literals
+            .iter()
+            .min_by_key(|l| {
+                occurrences.counts[l.as_index()] + occurrences.counts[(!**l).as_index()]
+            })
+            .expect("no empty clauses in the occurrence lists");
+
+        literals.iter().for_each(|l| marks[l.as_index()] = true);
+
+        let mut candidates = occurrences.clauses_with(min_literal);
+        candidates.extend(occurrences.clauses_with(!min_literal));
+
+        for d in candidates {
+            if d == c || occurrences.clauses[d].is_none() {
+                continue;
+            }
+
+            let other = occurrences.literals(d);
+            effort = effort.saturating_sub(other.len());
+            if other.len() < literals.len() {
+                continue;
+            }
+
+            let mut matched = 0;
+            let mut negated = vec![];
+            for literal in other {
+                if marks[literal.as_index()] {
+                    matched += 1;
+                } else if marks[(!*literal).as_index()] {
+                    negated.push(*literal);
+                }
+            }
+
+            if matched == literals.len() {
+                occurrences.remove_clause(bcp, d);
+                subsumed += 1;
+            } else if matched + 1 == literals.len() && negated.len() == 1 {
+                strengthen(bcp, &mut occurrences, d, negated[0]);
+                subsumed += 1;
+            }
+        }
+
+        literals.iter().for_each(|l| marks[l.as_index()] = false);
+    }
+
+    subsumed
+}
+
+/// Removes a literal from a clause, the strengthened clause is added before the old one is removed
+fn strengthen(
+    bcp: &mut BcpContext,
+    occurrences: &mut Occurrences,
+    clause: usize,
+    literal: Literal,
+) {
+    let literals = occurrences.literals(clause);
+
+    // units found earlier in the pass may have assigned literals of the clause
+    if !literals.iter().any(|l| bcp.assignment.literal_is_true(*l)) {
+        let strengthened = literals
+            .iter()
+            .copied()
+            .filter(|l| *l != literal && bcp.assignment.literal_is_unknown(*l))
+            .collect::<Vec<_>>();
+
+        let unit = strengthened.len() == 1;
+        occurrences.add_clause(bcp, strengthened);
+        if unit && propagate(bcp).is_err() {
+            bcp.set_unsat();
+        }
+    }
+
+    occurrences.remove_clause(bcp, clause);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_subsumed_clause() {
+        let cnf = CNF::from_dimacs("1 2 0\n1 2 3 0\n1 3 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        assert_eq!(subsume(&mut bcp, 1000), 1);
+
+        assert!(bcp.long_clauses.clauses()[0].header().deleted);
+        assert!(!bcp.long_clauses.clauses()[1].header().deleted);
+    }
+
+    #[test]
+    fn test_self_subsuming_resolution() {
+        // (1 v 2) and (-1 v 2 v 3) resolve to (2 v 3), which replaces the second clause
+        let cnf = CNF::from_dimacs("1 2 0\n-1 2 3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        assert_eq!(subsume(&mut bcp, 1000), 1);
+
+        assert!(bcp.long_clauses.clauses()[0].header().deleted);
+        assert!(bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(2), Literal::from_dimacs(3)]));
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::HashSet;
+
+use crate::bcp::{AddedClause, BcpContext};
+use crate::literal::Literal;
+
+/// Variables with more ternary clauses than this per polarity are not resolved on
+const OCCURRENCE_LIMIT: usize = 100;
+
+/// Hyper ternary resolution on the irredundant ternary clauses (Biere et al. 2017).
+///
+/// Every pair of ternary clauses `(a v b v c)` and `(-a v d v e)` is resolved. Binary resolvents
+/// are added as irredundant clauses and remove both antecedents, which they subsume. Ternary
+/// resolvents are added as learned clauses, which are kept by the reduction while they are used.
+/// Resolvents that are tautologies, longer than three literals or already in the formula are
+/// dropped.
+///
+/// The context has to be on the top decision level without satisfied clauses or false literals.
+/// The pass stops once `effort`, the number of resolved pairs, is used up. Returns the number of
+/// added resolvents.
+pub fn ternary_resolution(bcp: &mut BcpContext, mut effort: usize) -> usize {
+    let mut occurrences = vec![vec![]; bcp.assignment.variable_count() * 2];
+    let mut existing = HashSet::new();
+    for (index, clause) in bcp.long_clauses.clauses().iter().enumerate() {
+        if clause.header().deleted || clause.literals().len() != 3 {
+            continue;
+        }
+
+        let mut literals: [Literal; 3] = clause.literals().try_into().unwrap();
+        literals.sort_unstable();
+        existing.insert(literals);
+        if !clause.header().learned {
+            for literal in literals {
+                occurrences[literal.as_index()].push(index);
+            }
+        }
+    }
+
+    let mut resolvents = 0;
+    for variable in 0..bcp.assignment.variable_count() as u32 {
+        let positive = &occurrences[Literal::from_index(variable, true).as_index()];
+        let negative = &occurrences[Literal::from_index(variable, false).as_index()];
+        if positive.len() > OCCURRENCE_LIMIT || negative.len() > OCCURRENCE_LIMIT {
+            continue;
+        }
+
+        for &c in positive {
+            for &d in negative {
+                if effort == 0 {
+                    return resolvents;
+                }
+                effort -= 1;
+
+                let clauses = bcp.long_clauses.clauses();
+                if clauses[c].header().deleted || clauses[d].header().deleted {
+                    continue;
+                }
+
+                let mut resolvent = clauses[c]
+                    .literals()
+                    .iter()
+                    .chain(clauses[d].literals())
+                    .copied()
+                    .filter(|l| l.variable().index() != variable)
+                    .collect::<Vec<_>>();
+                resolvent.sort_unstable();
+                resolvent.dedup();
+
+                if resolvent.windows(2).any(|w| w[0] == !w[1]) {
+                    continue;
+                }
+
+                match *resolvent {
+                    [a, b] => {
+                        if !bcp.watch.contains_binary_clause([a, b]) {
+                            bcp.learn_clause(&resolvent);
+                            resolvents += 1;
+                        }
+                        for index in [c, d] {
+                            bcp.proof.delete_clause(bcp.long_clauses.literals(index));
+                            bcp.long_clauses.delete_clause(index);
+                        }
+                    }
+                    [a, b, e] if existing.insert([a, b, e]) => {
+                        if let AddedClause::Long(index) = bcp.learn_clause(&resolvent) {
+                            let header = bcp.long_clauses.find_clause_mut(index).header_mut();
+                            header.learned = true;
+                            header.glue = resolvent.len() as u32;
+                        }
+                        resolvents += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    resolvents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_binary_resolvent_subsumes_antecedents() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n-1 2 3 0\n1 4 5 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        assert_eq!(ternary_resolution(&mut bcp, 1000), 1);
+
+        assert!(bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(2), Literal::from_dimacs(3)]));
+        let deleted = bcp
+            .long_clauses
+            .clauses()
+            .iter()
+            .map(|c| c.header().deleted)
+            .collect::<Vec<_>>();
+        assert_eq!(deleted, vec![true, true, false]);
+    }
+
+    #[test]
+    fn test_ternary_resolvent() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n-1 2 4 0\n-2 5 6 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        // resolving on 2 gives clauses with four literals, which are dropped
+        assert_eq!(ternary_resolution(&mut bcp, 1000), 1);
+
+        let resolvent = bcp.long_clauses.clauses().last().unwrap();
+        assert!(resolvent.header().learned);
+        assert_eq!(
+            resolvent.literals(),
+            [2, 3, 4].map(Literal::from_dimacs).as_slice()
+        );
+    }
+}
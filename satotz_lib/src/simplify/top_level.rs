@@ -0,0 +1,123 @@
+use crate::bcp::trail::{Reason, TOP_DECISION_LEVEL};
+use crate::bcp::BcpContext;
+use crate::literal::Literal;
+
+/// Removes clauses satisfied on the top decision level and literals falsified on it.
+///
+/// Long clauses that shrink to two literals are moved into the binary clauses. Afterwards the
+/// deleted long clauses are collected, so the context has to be fully propagated on the top level.
+pub fn simplify_top_level(bcp: &mut BcpContext) {
+    assert_eq!(bcp.trail.current_decision_level(), TOP_DECISION_LEVEL);
+
+    let true_literals = bcp
+        .trail
+        .steps()
+        .iter()
+        .map(|s| s.assigned_literal)
+        .collect::<Vec<_>>();
+
+    // propagated units are added to the proof before their reasons are deleted
+    for step in bcp.trail.steps() {
+        if matches!(step.reason, Reason::Binary(_) | Reason::Long(_)) {
+            bcp.proof.add_clause(&[step.assigned_literal]);
+        }
+    }
+
+    // a binary clause with a false literal is satisfied by the other one after propagation
+    for literal in true_literals {
+        for other in bcp.watch.remove_binary_clauses_with(literal) {
+            bcp.proof.delete_clause(&[literal, other]);
+        }
+    }
+
+    for index in 0..bcp.long_clauses.clauses().len() {
+        let clause = &bcp.long_clauses.clauses()[index];
+        if clause.header().deleted {
+            continue;
+        }
+
+        let literals = clause.literals().to_vec();
+        if literals.iter().any(|l| bcp.assignment.literal_is_true(*l)) {
+            bcp.proof.delete_clause(&literals);
+            bcp.long_clauses.delete_clause(index);
+            continue;
+        }
+
+        // the watched literals are never false in a propagated clause, so they stay in front
+        let remaining = literals
+            .iter()
+            .copied()
+            .filter(|l| bcp.assignment.literal_is_unknown(*l))
+            .collect::<Vec<Literal>>();
+
+        if remaining.len() == literals.len() {
+            continue;
+        }
+
+        bcp.proof.add_clause(&remaining);
+        bcp.proof.delete_clause(&literals);
+
+        if let [a, b] = *remaining {
+            bcp.watch.add_binary_clause([a, b]);
+            bcp.long_clauses.delete_clause(index);
+        } else {
+            bcp.long_clauses.strengthen_clause(index, &remaining);
+        }
+    }
+
+    bcp.collect_garbage();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bcp::propagate;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_remove_satisfied_and_false_literals() {
+        let cnf = CNF::from_dimacs("1 0\n1 2 0\n-1 2 3 0\n1 4 5 0\n-1 3 4 5 0\n2 3 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        propagate(&mut bcp).unwrap();
+
+        simplify_top_level(&mut bcp);
+
+        assert!(!bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(1), Literal::from_dimacs(2)]));
+        assert!(bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(2), Literal::from_dimacs(3)]));
+
+        let long = bcp
+            .long_clauses
+            .clauses()
+            .iter()
+            .map(|c| {
+                let mut literals = c
+                    .literals()
+                    .iter()
+                    .map(|l| l.as_dimacs_integer())
+                    .collect::<Vec<_>>();
+                literals.sort_unstable();
+                literals
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(long, vec![vec![3, 4, 5], vec![2, 3, 4]]);
+    }
+
+    #[test]
+    fn test_propagation_after_simplification() {
+        let cnf = CNF::from_dimacs("1 0\n-1 2 3 4 0\n-2 5 6 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+        propagate(&mut bcp).unwrap();
+
+        simplify_top_level(&mut bcp);
+
+        crate::bcp::trail::decide_and_assign(&mut bcp, Literal::from_dimacs(-3));
+        crate::bcp::trail::decide_and_assign(&mut bcp, Literal::from_dimacs(-4));
+        propagate(&mut bcp).unwrap();
+
+        assert!(bcp.assignment.literal_is_true(Literal::from_dimacs(2)));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::bcp::BcpContext;
+use crate::literal::Literal;
+
+/// Removes binary clauses (a v b) for which -a already implies b over other binary clauses.
+///
+/// The search for an alternative path is bounded by `effort`, the number of binary implications
+/// that may be visited in total. Removed clauses are implied by the remaining ones, so they are
+/// only deleted from the proof.
+pub fn transitive_reduction(bcp: &mut BcpContext, mut effort: usize) {
+    let literal_count = bcp.assignment.variable_count() * 2;
+    let mut visited = vec![false; literal_count];
+
+    for code in 0..literal_count {
+        let a = Literal::from_code(code);
+        if !bcp.assignment.literal_is_unknown(a) {
+            continue;
+        }
+
+        let others = bcp.watch.binary_clauses(a).collect::<Vec<_>>();
+
+        for b in others {
+            // visit every clause only once
+            if b < a || !bcp.assignment.literal_is_unknown(b) {
+                continue;
+            }
+
+            if effort == 0 {
+                return;
+            }
+
+            bcp.watch.remove_binary_clause([a, b]);
+
+            if implies(bcp, !a, b, &mut visited, &mut effort) {
+                bcp.proof.delete_clause(&[a, b]);
+            } else {
+                bcp.watch.add_binary_clause([a, b]);
+            }
+        }
+    }
+}
+
+/// Searches a path from `from` to `to` in the binary implication graph
+fn implies(
+    bcp: &mut BcpContext,
+    from: Literal,
+    to: Literal,
+    visited: &mut [bool],
+    effort: &mut usize,
+) -> bool {
+    let mut stack = vec![from];
+    let mut seen = vec![from];
+    visited[from.as_index()] = true;
+
+    let mut found = false;
+
+    'search: while let Some(literal) = stack.pop() {
+        for implied in bcp.watch.binary_clauses(!literal) {
+            if *effort == 0 {
+                break 'search;
+            }
+            *effort -= 1;
+
+            if implied == to {
+                found = true;
+                break 'search;
+            }
+            if !std::mem::replace(&mut visited[implied.as_index()], true) {
+                seen.push(implied);
+                stack.push(implied);
+            }
+        }
+    }
+
+    for literal in seen {
+        visited[literal.as_index()] = false;
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    fn lits(a: i32, b: i32) -> [Literal; 2] {
+        [Literal::from_dimacs(a), Literal::from_dimacs(b)]
+    }
+
+    #[test]
+    fn test_remove_transitive_clause() {
+        // 1 -> 2 -> 3 makes (-1 v 3) redundant
+        let cnf = CNF::from_dimacs("-1 2 0\n-2 3 0\n-1 3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        transitive_reduction(&mut bcp, 100);
+
+        assert!(!bcp.watch.contains_binary_clause(lits(-1, 3)));
+        assert!(!bcp.watch.contains_binary_clause(lits(3, -1)));
+        assert!(bcp.watch.contains_binary_clause(lits(-1, 2)));
+        assert!(bcp.watch.contains_binary_clause(lits(-2, 3)));
+    }
+
+    #[test]
+    fn test_keep_cycle() {
+        // equivalent literals 1 = 2, no clause is redundant
+        let cnf = CNF::from_dimacs("-1 2 0\n-2 1 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        transitive_reduction(&mut bcp, 100);
+
+        assert!(bcp.watch.contains_binary_clause(lits(-1, 2)));
+        assert!(bcp.watch.contains_binary_clause(lits(-2, 1)));
+    }
+
+    #[test]
+    fn test_remove_duplicate() {
+        let cnf = CNF::from_dimacs("1 2 0\n1 2 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        transitive_reduction(&mut bcp, 100);
+
+        assert_eq!(bcp.watch.binary_clauses(Literal::from_dimacs(1)).count(), 1);
+    }
+
+    #[test]
+    fn test_effort_limit() {
+        let cnf = CNF::from_dimacs("-1 2 0\n-2 3 0\n-1 3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        transitive_reduction(&mut bcp, 0);
+
+        assert!(bcp.watch.contains_binary_clause(lits(-1, 3)));
+    }
+}
@@ -0,0 +1,188 @@
+use crate::assignment::AssignedValue;
+use crate::bcp::trail::TOP_DECISION_LEVEL;
+use crate::bcp::{propagate, trail, AddedClause, BcpContext};
+use crate::clause::ClauseIndex;
+use crate::literal::Literal;
+
+/// Result of vivifying a single clause
+#[derive(Debug, PartialEq)]
+enum Vivified {
+    Unchanged,
+    Satisfied,
+    Shortened(Vec<Literal>),
+}
+
+/// Clause vivification.
+///
+/// The negations of the literals of a clause are decided one after another. As soon as a literal
+/// of the clause is implied to be true, or propagation leads to a conflict, the decided literals
+/// (and the true literal) form a shorter clause that is implied by the formula. Literals that
+/// are implied to be false are dropped on the way.
+///
+/// Literals that occur in many clauses are decided first, so that the implications are shared
+/// with many other clauses. Learned clauses with low glue are vivified first, followed by the
+/// irredundant clauses. The pass stops as soon as propagation used more than `effort` ticks.
+/// Returns the number of shortened or removed clauses.
+pub fn vivify(bcp: &mut BcpContext, effort: u64) -> usize {
+    let tick_limit = bcp.ticks.saturating_add(effort);
+    let mut vivified = 0;
+
+    let mut occurrences = vec![0u32; bcp.assignment.variable_count() * 2];
+    for clause in bcp.long_clauses.clauses() {
+        if !clause.header().deleted {
+            for literal in clause.literals() {
+                occurrences[literal.as_index()] += 1;
+            }
+        }
+    }
+
+    for index in candidates(bcp) {
+        if bcp.is_unsat || bcp.ticks > tick_limit {
+            break;
+        }
+        if bcp.long_clauses.clauses()[index].header().deleted {
+            continue;
+        }
+
+        match vivify_clause(bcp, index, &occurrences) {
+            Vivified::Unchanged => continue,
+            Vivified::Satisfied => {
+                let literals = bcp.long_clauses.literals(index).to_vec();
+                bcp.proof.delete_clause(&literals);
+                bcp.long_clauses.delete_clause(index);
+            }
+            Vivified::Shortened(literals) => replace_clause(bcp, index, &literals),
+        }
+        vivified += 1;
+    }
+
+    vivified
+}
+
+/// Returns the long clauses to vivify, learned clauses ordered by glue and size come first
+fn candidates(bcp: &BcpContext) -> Vec<ClauseIndex> {
+    let clauses = bcp.long_clauses.clauses();
+
+    let mut candidates = (0..clauses.len())
+        .filter(|i| !clauses[*i].header().deleted)
+        .collect::<Vec<_>>();
+
+    candidates.sort_by_key(|i| {
+        let clause = &clauses[*i];
+        (
+            !clause.header().learned,
+            clause.header().glue,
+            clause.literals().len(),
+        )
+    });
+
+    candidates
+}
+
+/// Vivifies a long clause, the context has to be fully propagated on the top decision level
+fn vivify_clause(bcp: &mut BcpContext, index: ClauseIndex, occurrences: &[u32]) -> Vivified {
+    let mut literals = bcp.long_clauses.literals(index).to_vec();
+
+    if literals.iter().any(|l| bcp.assignment.literal_is_true(*l)) {
+        return Vivified::Satisfied;
+    }
+
+    literals.sort_by_key(|l| std::cmp::Reverse(occurrences[l.as_index()]));
+
+    bcp.ignored_clause = Some(index);
+
+    let mut vivified = vec![];
+    for &literal in &literals {
+        match bcp.assignment.literal_value(literal) {
+            // implied by the decided literals
+            AssignedValue::True => {
+                vivified.push(literal);
+                break;
+            }
+            // the literal is redundant
+            AssignedValue::False => {}
+            AssignedValue::Unknown => {
+                vivified.push(literal);
+                trail::decide_and_assign(bcp, !literal);
+                if propagate(bcp).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    if bcp.trail.current_decision_level() != TOP_DECISION_LEVEL {
+        trail::backtrack_without_phases(bcp, TOP_DECISION_LEVEL);
+    }
+    bcp.ignored_clause = None;
+
+    if vivified.len() < literals.len() {
+        Vivified::Shortened(vivified)
+    } else {
+        Vivified::Unchanged
+    }
+}
+
+/// Replaces a long clause by its vivified version, keeping the metadata of learned clauses
+fn replace_clause(bcp: &mut BcpContext, index: ClauseIndex, literals: &[Literal]) {
+    let header = *bcp.long_clauses.clauses()[index].header();
+    let old_literals = bcp.long_clauses.literals(index).to_vec();
+
+    match bcp.learn_clause(literals) {
+        AddedClause::Long(new_index) => {
+            let new_header = bcp.long_clauses.find_clause_mut(new_index).header_mut();
+            new_header.learned = header.learned;
+            new_header.glue = header.glue.min(literals.len() as u32 - 1);
+        }
+        AddedClause::Unit if propagate(bcp).is_err() => bcp.set_unsat(),
+        _ => {}
+    }
+
+    bcp.proof.delete_clause(&old_literals);
+    bcp.long_clauses.delete_clause(index);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cnf::CNF;
+
+    #[test]
+    fn test_implied_literal() {
+        // -1 implies -2, so 2 is redundant in (1 v 2 v 3 v 4)
+        let cnf = CNF::from_dimacs("1 -2 0\n1 2 3 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        assert_eq!(
+            vivify_clause(&mut bcp, 0, &[0; 8]),
+            Vivified::Shortened(vec![
+                Literal::from_dimacs(1),
+                Literal::from_dimacs(3),
+                Literal::from_dimacs(4)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_conflict() {
+        // -1 and -2 lead to a conflict, so (1 v 2) is implied
+        let cnf = CNF::from_dimacs("1 2 5 0\n1 2 -5 0\n1 2 3 4 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        assert!(vivify(&mut bcp, 1000) > 0);
+
+        assert!(bcp
+            .watch
+            .contains_binary_clause([Literal::from_dimacs(1), Literal::from_dimacs(2)]));
+        assert!(bcp.long_clauses.clauses()[2].header().deleted);
+    }
+
+    #[test]
+    fn test_unchanged() {
+        let cnf = CNF::from_dimacs("1 2 3 0\n");
+        let mut bcp = BcpContext::from_cnf(&cnf);
+
+        assert_eq!(vivify_clause(&mut bcp, 0, &[0; 6]), Vivified::Unchanged);
+        assert_eq!(bcp.trail.current_decision_level(), TOP_DECISION_LEVEL);
+    }
+}
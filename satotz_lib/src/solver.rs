@@ -1,24 +1,43 @@
+pub use crate::analyze::LearningScheme;
 use crate::assignment::AssignedValue;
+use crate::bcp::trail;
 use crate::cnf::CNF;
-use crate::literal::Literal;
+pub use crate::literal::{Literal, Variable};
+use crate::proof::Proof;
+use crate::reconstruct::ReconstructionStack;
 use crate::resize::Resize;
-use crate::search::{search, SearchContext};
+use crate::search::heuristic::CustomHeuristic;
+pub use crate::search::heuristic::{DecisionContext, DecisionHeuristic};
+pub use crate::search::mode::{Decisions, Mode, ModePolicy};
+pub use crate::search::policy::{ClauseReductionPolicy, ConflictInfo, RestartPolicy};
+pub use crate::search::restart::Restarts;
+use crate::search::{prioritize_queue, search, SearchContext};
+use crate::simplify::simplify;
+use crate::statistics::Statistics;
+use std::io::Write;
 
+/// Variables passed to and returned by the solver are the variables of the user. They are mapped
+/// to the variables of the search, which also contain the auxiliary variables introduced by bounded
+/// variable addition, so variables added later take the indices after them in the search.
 #[derive(Default, Debug)]
 pub struct Solver {
     search: SearchContext,
-    variable_count: usize,
+    /// maps a variable of the user to its variable in the search
+    internal: Vec<Variable>,
+    /// maps a variable of the search to its variable of the user, `None` for auxiliary variables
+    external: Vec<Option<Variable>>,
+    /// model of the original formula found by the last solve, cleared when the formula changes
+    model: Option<Vec<Literal>>,
 }
 
 impl Solver {
     pub fn from_cnf(cnf: CNF) -> Solver {
-        let mut solver = Solver {
-            search: Default::default(),
-            variable_count: cnf.variable_count(),
-        };
+        let mut solver = Solver::default();
 
-        solver.search.use_dlis = true;
-        solver.search.resize(solver.variable_count);
+        solver.search.reconstruction = ReconstructionStack::new(cnf.variable_count());
+        if cnf.variable_count() > 0 {
+            solver.add_variables(Variable::from_index(cnf.variable_count() as u32 - 1));
+        }
 
         for c in cnf.clauses().iter() {
             solver.add_clause(c.literals());
@@ -31,20 +50,329 @@ impl Solver {
         Self::from_cnf(CNF::from_clauses(&clauses))
     }
 
-    pub fn without_dlis(mut self) -> Self {
-        self.search.use_dlis = false;
+    /// Uses the given decision heuristic, restart policy and phases in focused mode
+    pub fn with_focused_policy(mut self, policy: ModePolicy) -> Self {
+        let var_count = self.search.bcp.assignment.variable_count();
+        self.search
+            .modes
+            .set_policy(Mode::Focused, policy, var_count);
+        prioritize_queue(&mut self.search, Mode::Focused);
+        self
+    }
+
+    /// Uses the given decision heuristic, restart policy and phases in stable mode
+    pub fn with_stable_policy(mut self, policy: ModePolicy) -> Self {
+        let var_count = self.search.bcp.assignment.variable_count();
+        self.search
+            .modes
+            .set_policy(Mode::Stable, policy, var_count);
+        prioritize_queue(&mut self.search, Mode::Stable);
+        self
+    }
+
+    /// Decides with the given heuristic in the given mode instead of the decision heuristic of
+    /// its policy. Boxed heuristics can be passed as well. A later policy for the mode replaces
+    /// the heuristic.
+    pub fn with_decision_heuristic(
+        mut self,
+        mode: Mode,
+        heuristic: impl DecisionHeuristic + 'static,
+    ) -> Self {
+        let var_count = self.search.bcp.assignment.variable_count();
+        let heuristic = CustomHeuristic::new(Box::new(heuristic));
+        self.search.modes.set_heuristic(mode, heuristic, var_count);
+        prioritize_queue(&mut self.search, mode);
+        self
+    }
+
+    /// Restarts with the given policy in the given mode instead of the restarts of its policy.
+    /// Boxed policies can be passed as well. A later policy for the mode replaces it.
+    pub fn with_restart_policy(
+        mut self,
+        mode: Mode,
+        restarts: impl RestartPolicy + 'static,
+    ) -> Self {
+        self.search
+            .modes
+            .set_restart_policy(mode, Box::new(restarts));
+        self
+    }
+
+    /// Reduces the learned clauses when the given policy asks for it
+    pub fn with_reduction_policy(
+        mut self,
+        reduction: impl ClauseReductionPolicy + 'static,
+    ) -> Self {
+        self.search.reduction = Box::new(reduction);
+        self
+    }
+
+    /// Decides the first unassigned variable instead of using DLIS in both modes
+    pub fn without_dlis(self) -> Self {
+        let first_unassigned = |policy: ModePolicy| ModePolicy {
+            decisions: Decisions::FirstUnassigned,
+            ..policy
+        };
+        self.with_focused_policy(first_unassigned(ModePolicy::focused()))
+            .with_stable_policy(first_unassigned(ModePolicy::stable()))
+    }
+
+    /// Stays in the given mode instead of alternating between the focused and stable mode
+    pub fn with_single_mode(mut self, mode: Mode) -> Self {
+        self.search.modes.mode = mode;
+        self.search.modes.switching.disabled = true;
+        self
+    }
+
+    /// Derives learned clauses with the given scheme instead of the first UIP
+    pub fn with_learning_scheme(mut self, scheme: LearningScheme) -> Self {
+        self.search.conflict_analysis.scheme = scheme;
+        self
+    }
+
+    /// Writes a DRAT proof of all derived and deleted clauses to the given writer, which should be
+    /// buffered
+    pub fn with_proof(mut self, writer: impl Write + 'static) -> Self {
+        self.search.bcp.proof = Proof::new(Box::new(writer));
         self
     }
 
-    /// Adds a clause to the formula, can break invariants if introducing new variables
+    /// Adds a clause to the formula, including its new variables.
+    ///
+    /// The clause is normalized first: literals are sorted and deduplicated, literals false on the
+    /// top level are removed, and tautologies, clauses satisfied on the top level and clauses that
+    /// are already in the formula are dropped. Clauses removed by the elimination of a variable in
+    /// the clause are restored.
     pub fn add_clause(&mut self, clause: &[Literal]) {
-        self.search.bcp.add_clause(clause);
+        if let Some(variable) = clause
+            .iter()
+            .map(|l| l.variable())
+            .max_by_key(|v| v.index())
+        {
+            self.add_variables(variable);
+        }
+
+        let clause = clause
+            .iter()
+            .map(|l| self.internal_literal(*l))
+            .collect::<Vec<_>>();
+        self.add_internal_clause(&clause);
+    }
+
+    /// Adds a clause over the variables of the search, see `add_clause`
+    fn add_internal_clause(&mut self, clause: &[Literal]) {
+        self.model = None;
+        if self.search.bcp.trail.current_decision_level() != trail::TOP_DECISION_LEVEL {
+            trail::backtrack(&mut self.search.bcp, trail::TOP_DECISION_LEVEL);
+        }
+
+        for literal in clause {
+            self.restore(literal.variable());
+        }
+
+        let bcp = &mut self.search.bcp;
+        let stats = &mut self.search.stats;
+
+        let mut literals = clause.to_vec();
+        literals.sort_unstable();
+        literals.dedup();
+        stats.duplicate_literals += (clause.len() - literals.len()) as u64;
+
+        // a literal and its negation are next to each other after sorting
+        if literals.windows(2).any(|w| w[0] == !w[1]) {
+            stats.tautologies += 1;
+            return;
+        }
+
+        if literals.iter().any(|l| bcp.assignment.literal_is_true(*l)) {
+            stats.satisfied_clauses += 1;
+            return;
+        }
+
+        let sorted_len = literals.len();
+        literals.retain(|l| bcp.assignment.literal_is_unknown(*l));
+        let false_literals = sorted_len - literals.len();
+        stats.false_literals += false_literals as u64;
+
+        if literals.len() >= 2 && bcp.contains_clause(&literals) {
+            stats.duplicate_clauses += 1;
+            return;
+        }
+
+        if false_literals > 0 {
+            bcp.learn_clause(&literals);
+        } else {
+            bcp.add_clause(&literals);
+        }
+        self.search.dlis.invalidate();
+    }
+
+    /// Prevents a variable of the formula from being eliminated, e.g. because it will be used in
+    /// later clauses. Every call has to be matched by a call to `melt`. A new variable is added to
+    /// the formula, like in `add_clause`.
+    pub fn freeze(&mut self, variable: Variable) {
+        self.add_variables(variable);
+        let variable = self.internal[variable.as_index()];
+        self.restore(variable);
+        self.search.frozen[variable.as_index()] += 1;
+    }
+
+    /// Allows the elimination of a variable again, once it is melted as often as it was frozen
+    pub fn melt(&mut self, variable: Variable) {
+        let frozen = self.internal.get(variable.as_index());
+        match frozen.map(|v| &mut self.search.frozen[v.as_index()]) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => panic!("melting a variable that is not frozen"),
+        }
+    }
+
+    /// Decides the variable of the literal with the literal's polarity instead of its saved or
+    /// target phase, also in all later solves. A new variable is added to the formula, like in
+    /// `add_clause`.
+    pub fn set_phase(&mut self, literal: Literal) {
+        self.add_variables(literal.variable());
+        let literal = self.internal_literal(literal);
+        self.search.bcp.phases.forced[literal.variable().as_index()] = Some(literal.is_positive());
+    }
+
+    /// Raises the score of the variable in the decision heuristics of both modes, also in all
+    /// later solves. Activity-based heuristics score the priority like that many conflicts, the
+    /// scoring heuristics like that many unresolved clauses, VMTF decides variables with a raised
+    /// priority first and custom heuristics get the priority. Deciding the first unassigned
+    /// variable ignores it. Replaces the previous priority of the variable, which is zero
+    /// initially. A new variable is added to the formula, like in `add_clause`.
+    pub fn set_priority(&mut self, variable: Variable, priority: f64) {
+        self.add_variables(variable);
+        let variable = self.internal[variable.as_index()];
+        self.search.hints.set_priority(variable, priority);
+    }
+
+    /// Decides the given variables first, in the given order and before the decision heuristic is
+    /// asked, also in all later solves. Replaces the previous order. New variables are added to
+    /// the formula, like in `add_clause`.
+    pub fn set_decision_order(&mut self, variables: &[Variable]) {
+        if let Some(&variable) = variables.iter().max_by_key(|v| v.index()) {
+            self.add_variables(variable);
+        }
+        let variables = variables
+            .iter()
+            .map(|v| self.internal[v.as_index()])
+            .collect::<Vec<_>>();
+        self.search.hints.set_order(&variables);
+    }
+
+    /// Adds the variables up to the given one to the formula if they are new, each with the next
+    /// variable of the search
+    fn add_variables(&mut self, variable: Variable) {
+        let variable_count = variable.as_index() + 1;
+        if variable_count <= self.internal.len() {
+            return;
+        }
+
+        let mut internal_count = self.search.bcp.assignment.variable_count();
+        for index in self.internal.len()..variable_count {
+            self.internal
+                .push(Variable::from_index(internal_count as u32));
+            self.external.push(Some(Variable::from_index(index as u32)));
+            internal_count += 1;
+        }
+        self.search.resize(internal_count);
+    }
+
+    fn internal_literal(&self, literal: Literal) -> Literal {
+        Literal::from_variable(
+            &self.internal[literal.variable().as_index()],
+            literal.is_positive(),
+        )
+    }
+
+    /// Maps the variables of the search to the variables of the simplified formula, the variables
+    /// of the user keep their indices and the auxiliary variables follow them
+    fn exported_variables(&self) -> Vec<Variable> {
+        let mut auxiliary_count = 0;
+        self.external
+            .iter()
+            .map(|variable| {
+                variable.unwrap_or_else(|| {
+                    auxiliary_count += 1;
+                    Variable::from_index((self.internal.len() + auxiliary_count - 1) as u32)
+                })
+            })
+            .collect()
+    }
+
+    /// Adds the clauses removed by the elimination of the variable of the search to the formula
+    /// again
+    fn restore(&mut self, variable: Variable) {
+        for clause in self.search.reconstruction.restore(variable) {
+            self.add_internal_clause(&clause);
+        }
+    }
+
+    /// Simplifies the formula on the top decision level, e.g. by probing for failed literals.
+    /// Auxiliary variables introduced by the simplification are hidden from the assignment, and
+    /// eliminated variables are reconstructed in it.
+    pub fn simplify(&mut self) {
+        self.model = None;
+        simplify(
+            &mut self.search.bcp,
+            &mut self.search.reconstruction,
+            &self.search.frozen,
+        );
+        let variable_count = self.search.bcp.assignment.variable_count();
+        self.search.resize(variable_count);
+        self.external.resize(variable_count, None);
+    }
+
+    /// Returns the clauses removed by variable elimination, over the variables of
+    /// `simplified_cnf`
+    pub fn reconstruction_stack(&self) -> ReconstructionStack {
+        self.search
+            .reconstruction
+            .renumbered(&self.exported_variables(), self.internal.len())
+    }
+
+    /// Returns the current irredundant formula, including top level units and auxiliary
+    /// variables, which follow the variables of the original formula. A model of it is extended
+    /// to a model of the original formula by the reconstruction stack.
+    pub fn simplified_cnf(&mut self) -> CNF {
+        let variables = self.exported_variables();
+        let bcp = &mut self.search.bcp;
+        let variable_count = bcp.assignment.variable_count();
+
+        if bcp.trail.current_decision_level() != trail::TOP_DECISION_LEVEL {
+            trail::backtrack(bcp, trail::TOP_DECISION_LEVEL);
+        }
+
+        if bcp.is_unsat {
+            return CNF::from_clauses(&[vec![]]).with_variable_count(variable_count);
+        }
+
+        let clauses = bcp
+            .irredundant_clauses()
+            .iter()
+            .map(|c| {
+                c.iter()
+                    .map(|l| {
+                        let variable = variables[l.variable().as_index()];
+                        Literal::from_variable(&variable, l.is_positive()).as_dimacs_integer()
+                    })
+                    .collect()
+            })
+            .collect::<Vec<Vec<i32>>>();
+
+        CNF::from_clauses(&clauses).with_variable_count(variable_count)
+    }
+
+    /// Returns the statistics collected so far
+    pub fn statistics(&self) -> &Statistics {
+        &self.search.stats
     }
 
     /// Check satisfiability of the formula
     pub fn solve(&mut self) -> bool {
         loop {
-            if let Some(result) = search(&mut self.search) {
+            if let (_, Some(result)) = self.step() {
                 return result;
             }
         }
@@ -52,29 +380,62 @@ impl Solver {
 
     pub fn step(&mut self) -> (&mut Self, Option<bool>) {
         let step_result = search(&mut self.search);
+        self.model = (step_result == Some(true)).then(|| self.extended_assignment());
         (self, step_result)
     }
 
-    /// Returns the current assignment, literals with unknown value are falsified
+    /// Returns the model found by the last solve if the formula did not change since. Otherwise
+    /// returns the current assignment of the formulas variables, where literals with unknown value
+    /// are falsified.
     pub fn assignment(&self) -> Vec<Literal> {
-        self.search.bcp.assignment.assignment()
+        match &self.model {
+            Some(model) => model.clone(),
+            None => self.extended_assignment(),
+        }
     }
 
-    /// Returns the value assigned to a literal
+    /// Returns the value of a literal in the model found by the last solve if the formula did not
+    /// change since. Otherwise returns its value in the current assignment, or `None` if its
+    /// variable is unassigned.
     pub fn value_of(&self, literal: Literal) -> Option<bool> {
-        match self.search.bcp.assignment.literal_value(literal) {
+        if let Some(model) = &self.model {
+            return model
+                .get(literal.variable().as_index())
+                .map(|l| *l == literal);
+        }
+
+        let variable = *self.internal.get(literal.variable().as_index())?;
+        let internal = Literal::from_variable(&variable, literal.is_positive());
+        match self.search.bcp.assignment.literal_value(internal) {
             AssignedValue::True => Some(true),
             AssignedValue::False => Some(false),
             AssignedValue::Unknown => None,
         }
     }
+
+    /// Extends the current assignment of the search by the reconstruction stack and maps it to
+    /// the variables of the user
+    fn extended_assignment(&self) -> Vec<Literal> {
+        let mut assignment = self.search.bcp.assignment.assignment();
+        self.search.reconstruction.extend(&mut assignment);
+        self.internal
+            .iter()
+            .enumerate()
+            .map(|(index, variable)| {
+                let positive = assignment[variable.as_index()].is_positive();
+                Literal::from_index(index as u32, positive)
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::cell::RefCell;
     use std::fs;
     use std::path::PathBuf;
+    use std::rc::Rc;
 
     #[test]
     fn test_formula() {
@@ -94,4 +455,547 @@ mod tests {
             assert_eq!(sat, file.file_name().to_str().unwrap().contains(".sat"));
         }
     }
+
+    #[test]
+    fn test_all_formulas_simplified() {
+        for entry in fs::read_dir(PathBuf::from("../test_formulas")).unwrap() {
+            let file = entry.unwrap();
+            let cnf = CNF::from_file(file.path());
+            let mut solver = Solver::from_cnf(cnf.clone()).without_dlis();
+            solver.simplify();
+            let sat = solver.solve();
+            assert_eq!(sat, file.file_name().to_str().unwrap().contains(".sat"));
+            if sat {
+                assert_eq!(solver.assignment().len(), cnf.variable_count());
+                assert_model(&cnf, &solver);
+            }
+        }
+    }
+
+    #[test]
+    fn test_mode_policies() {
+        let policies = [
+            ModePolicy::focused(),
+            ModePolicy::stable(),
+            ModePolicy {
+                decisions: Decisions::Chb,
+                restarts: Restarts::Glue,
+                target_phases: false,
+            },
+            ModePolicy {
+                decisions: Decisions::Lrb,
+                restarts: Restarts::Luby,
+                target_phases: true,
+            },
+            ModePolicy {
+                decisions: Decisions::Dlis,
+                restarts: Restarts::Never,
+                target_phases: false,
+            },
+            ModePolicy {
+                decisions: Decisions::FirstUnassigned,
+                restarts: Restarts::Luby,
+                target_phases: true,
+            },
+            ModePolicy {
+                decisions: Decisions::JeroslowWang,
+                restarts: Restarts::Glue,
+                target_phases: false,
+            },
+            ModePolicy {
+                decisions: Decisions::TwoSidedJeroslowWang,
+                restarts: Restarts::Luby,
+                target_phases: true,
+            },
+            ModePolicy {
+                decisions: Decisions::Moms,
+                restarts: Restarts::Never,
+                target_phases: false,
+            },
+            ModePolicy {
+                decisions: Decisions::Bohm,
+                restarts: Restarts::Glue,
+                target_phases: true,
+            },
+        ];
+
+        for entry in fs::read_dir(PathBuf::from("../test_formulas")).unwrap() {
+            let file = entry.unwrap();
+            let cnf = CNF::from_file(file.path());
+            if cnf.clauses().len() >= 1000 {
+                continue;
+            }
+            for policy in &policies {
+                let mut solver = Solver::from_cnf(CNF::from_file(file.path()))
+                    .with_stable_policy(*policy)
+                    .with_single_mode(Mode::Stable);
+                let sat = solver.solve();
+                assert_eq!(sat, file.file_name().to_str().unwrap().contains(".sat"));
+                if sat {
+                    assert_model(&cnf, &solver);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_vmtf_and_vsids_reuse_trail() {
+        let file = "../test_formulas/add64.unsat";
+        let mut solver = Solver::from_cnf(CNF::from_file_str(file))
+            .with_focused_policy(ModePolicy::focused())
+            .with_stable_policy(ModePolicy::stable());
+        assert!(!solver.solve());
+        let stats = solver.statistics();
+        assert!(stats.reused_trails > 0);
+        assert!(stats.reused_levels >= stats.reused_trails);
+    }
+
+    /// Decides the unassigned variable with the highest index false, tracking the assignment
+    /// through the hooks
+    #[derive(Debug, Default)]
+    struct LastVariableFalse {
+        assigned: Vec<bool>,
+        conflicts: usize,
+    }
+
+    impl DecisionHeuristic for LastVariableFalse {
+        fn resize(&mut self, variable_count: usize) {
+            self.assigned.resize(variable_count, false);
+        }
+
+        fn assigned(&mut self, literal: Literal) {
+            self.assigned[literal.variable().as_index()] = true;
+        }
+
+        fn unassigned(&mut self, literal: Literal) {
+            self.assigned[literal.variable().as_index()] = false;
+        }
+
+        fn conflict(&mut self, variables: &[Variable]) {
+            assert!(!variables.is_empty());
+            self.conflicts += 1;
+        }
+
+        fn decide(&mut self, context: &DecisionContext) -> Option<Literal> {
+            let index = self.assigned.iter().rposition(|assigned| !assigned)?;
+            let variable = Variable::from_index(index as u32);
+            assert!(context.is_unassigned(variable));
+            Some(Literal::from_variable(&variable, false))
+        }
+    }
+
+    #[test]
+    fn test_scoring_keeps_phase() {
+        // the scoring heuristics decide -1, which satisfies all clauses, the saved phase would
+        // propagate 2, 3, 4
+        for decisions in [
+            Decisions::Dlis,
+            Decisions::JeroslowWang,
+            Decisions::TwoSidedJeroslowWang,
+            Decisions::Moms,
+            Decisions::Bohm,
+        ] {
+            let policy = ModePolicy {
+                decisions,
+                ..ModePolicy::focused()
+            };
+            let mut solver = Solver::from_clauses(vec![vec![-1, 2], vec![-1, 3], vec![-1, 4]])
+                .with_focused_policy(policy)
+                .with_stable_policy(policy);
+            assert!(solver.solve());
+            assert_eq!(
+                solver.value_of(Literal::from_dimacs(1)),
+                Some(false),
+                "{decisions:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_decision_heuristics() {
+        for entry in fs::read_dir(PathBuf::from("../test_formulas")).unwrap() {
+            let file = entry.unwrap();
+            let cnf = CNF::from_file(file.path());
+            if cnf.clauses().len() >= 1000 {
+                continue;
+            }
+            let boxed: Box<dyn DecisionHeuristic> = Box::<LastVariableFalse>::default();
+            let mut solver = Solver::from_cnf(CNF::from_file(file.path()))
+                .with_decision_heuristic(Mode::Focused, LastVariableFalse::default())
+                .with_decision_heuristic(Mode::Stable, boxed);
+            let sat = solver.solve();
+            assert_eq!(sat, file.file_name().to_str().unwrap().contains(".sat"));
+            if sat {
+                assert_model(&cnf, &solver);
+            }
+        }
+    }
+
+    /// Restarts and reduces after fixed conflict intervals, counting the calls of the hooks
+    #[derive(Debug, Default)]
+    struct FixedInterval {
+        interval: u64,
+        conflicts: u64,
+        last_conflicts: u64,
+        calls: Rc<RefCell<u64>>,
+    }
+
+    impl FixedInterval {
+        fn conflict(&mut self, conflict: &ConflictInfo) {
+            assert!(conflict.glue >= 1);
+            assert!(conflict.trail_length >= 1);
+            self.conflicts = conflict.conflicts;
+        }
+
+        fn is_due(&mut self) -> bool {
+            self.conflicts >= self.last_conflicts + self.interval
+        }
+
+        fn finished(&mut self) {
+            self.last_conflicts = self.conflicts;
+            *self.calls.borrow_mut() += 1;
+        }
+    }
+
+    impl RestartPolicy for FixedInterval {
+        fn conflict(&mut self, conflict: &ConflictInfo) {
+            FixedInterval::conflict(self, conflict);
+        }
+
+        fn should_restart(&mut self) -> bool {
+            self.is_due()
+        }
+
+        fn restarted(&mut self) {
+            self.finished();
+        }
+    }
+
+    impl ClauseReductionPolicy for FixedInterval {
+        fn conflict(&mut self, conflict: &ConflictInfo) {
+            FixedInterval::conflict(self, conflict);
+        }
+
+        fn should_reduce(&mut self) -> bool {
+            self.is_due()
+        }
+
+        fn reduced(&mut self, _deleted: usize) {
+            self.finished();
+        }
+    }
+
+    #[test]
+    fn test_restart_and_reduction_policies() {
+        let file = "../test_formulas/add32.unsat";
+        let restarts = FixedInterval {
+            interval: 10,
+            ..Default::default()
+        };
+        let reductions = FixedInterval {
+            interval: 100,
+            ..Default::default()
+        };
+        let (restart_calls, reduction_calls) = (restarts.calls.clone(), reductions.calls.clone());
+        let boxed: Box<dyn ClauseReductionPolicy> = Box::new(reductions);
+
+        let mut solver = Solver::from_cnf(CNF::from_file_str(file))
+            .with_restart_policy(Mode::Focused, restarts)
+            .with_single_mode(Mode::Focused)
+            .with_reduction_policy(boxed);
+        assert!(!solver.solve());
+
+        let stats = solver.statistics();
+        assert!(stats.conflicts >= 100);
+        assert_eq!(stats.restarts, *restart_calls.borrow());
+        assert!(stats.restarts > 0 && stats.restarts <= stats.conflicts / 10);
+        assert_eq!(stats.reductions, *reduction_calls.borrow());
+        assert!(stats.reductions > 0 && stats.reductions <= stats.conflicts / 100);
+    }
+
+    #[test]
+    fn test_decision_hints() {
+        // every decision is in the order, so the first model is determined by it and the phases
+        let mut solver = Solver::from_clauses(vec![vec![1, 2, 3], vec![-1, -2], vec![-3, 4]]);
+        solver.set_decision_order(&[3, 4, 2, 1].map(Variable::from_dimacs));
+        solver.set_phase(Literal::from_dimacs(-3));
+        solver.set_phase(Literal::from_dimacs(-4));
+        solver.set_phase(Literal::from_dimacs(2));
+        assert!(solver.solve());
+        assert_eq!(
+            solver.assignment(),
+            [-1, 2, -3, -4].map(Literal::from_dimacs)
+        );
+
+        // the hints are kept for later solves
+        solver.add_clause(&[Literal::from_dimacs(4)]);
+        assert!(solver.solve());
+        assert_eq!(solver.value_of(Literal::from_dimacs(2)), Some(true));
+        assert_eq!(solver.value_of(Literal::from_dimacs(3)), Some(false));
+
+        for entry in fs::read_dir(PathBuf::from("../test_formulas")).unwrap() {
+            let file = entry.unwrap();
+            let cnf = CNF::from_file(file.path());
+            let mut solver = Solver::from_cnf(CNF::from_file(file.path()));
+            let variables = (1..=cnf.variable_count().min(8) as i32).map(Variable::from_dimacs);
+            for (i, variable) in variables.enumerate() {
+                solver.set_priority(variable, (i % 3) as f64);
+                solver.set_phase(Literal::from_variable(&variable, i % 2 == 0));
+            }
+            let sat = solver.solve();
+            assert_eq!(sat, file.file_name().to_str().unwrap().contains(".sat"));
+            if sat {
+                assert_model(&cnf, &solver);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hints_for_new_variables() {
+        let mut solver = Solver::from_clauses(vec![vec![1, 2]]);
+        solver.set_phase(Literal::from_dimacs(-3));
+        solver.set_priority(Variable::from_dimacs(4), 1.0);
+        solver.set_decision_order(&[Variable::from_dimacs(5)]);
+        solver.set_phase(Literal::from_dimacs(-5));
+        assert!(solver.solve());
+        assert_eq!(solver.assignment().len(), 5);
+        assert_eq!(solver.value_of(Literal::from_dimacs(5)), Some(false));
+    }
+
+    #[test]
+    fn test_learning_schemes() {
+        let schemes = [LearningScheme::LastUip, LearningScheme::AllDecisions];
+
+        for entry in fs::read_dir(PathBuf::from("../test_formulas")).unwrap() {
+            let file = entry.unwrap();
+            let cnf = CNF::from_file(file.path());
+            // clauses of decisions only are long and weak on large formulas
+            if cnf.clauses().len() >= 1000 {
+                continue;
+            }
+            for scheme in schemes {
+                let mut solver =
+                    Solver::from_cnf(CNF::from_file(file.path())).with_learning_scheme(scheme);
+                let sat = solver.solve();
+                assert_eq!(sat, file.file_name().to_str().unwrap().contains(".sat"));
+                if sat {
+                    assert_model(&cnf, &solver);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalize_added_clauses() {
+        let mut solver = Solver::from_clauses(vec![
+            vec![1, 2, 1],
+            vec![1, -1, 3],
+            vec![-4],
+            vec![4, 2, 5],
+            vec![2, 1],
+            vec![3, -4, 4],
+            vec![-3, 2, 4],
+            vec![2, -3],
+        ]);
+
+        let stats = solver.statistics().clone();
+        assert_eq!(stats.duplicate_literals, 1);
+        assert_eq!(stats.tautologies, 2);
+        assert_eq!(stats.satisfied_clauses, 0);
+        assert_eq!(stats.false_literals, 2);
+        assert_eq!(stats.duplicate_clauses, 2);
+
+        solver.add_clause(&[Literal::from_dimacs(-4), Literal::from_dimacs(5)]);
+        assert_eq!(solver.statistics().satisfied_clauses, 1);
+
+        assert!(solver.solve());
+    }
+
+    #[test]
+    fn test_duplicate_clauses_with_high_degree_literal() {
+        // all clauses contain the variable 1, duplicates are found without visiting its clauses
+        let count = 20_000;
+        let clauses = (3..count + 3)
+            .flat_map(|i| [vec![1, i], vec![1, 2, i], vec![-1, 2, -i, i + 1]])
+            .collect::<Vec<_>>();
+        let mut solver = Solver::from_clauses(clauses.clone());
+        assert_eq!(solver.statistics().duplicate_clauses, 0);
+
+        for clause in &clauses {
+            let literals = clause.iter().rev().map(|&l| Literal::from_dimacs(l));
+            solver.add_clause(&literals.collect::<Vec<_>>());
+        }
+        assert_eq!(solver.statistics().duplicate_clauses, clauses.len() as u64);
+        assert!(solver.solve());
+    }
+
+    #[test]
+    fn test_simplified_cnf_postsolve() {
+        for entry in fs::read_dir(PathBuf::from("../test_formulas")).unwrap() {
+            let file = entry.unwrap();
+            let cnf = CNF::from_file(file.path());
+            let mut solver = Solver::from_cnf(CNF::from_file(file.path()));
+            solver.simplify();
+
+            let simplified = CNF::from_dimacs(&solver.simplified_cnf().to_dimacs());
+            let mut simplified_solver = Solver::from_cnf(simplified);
+            let sat = simplified_solver.solve();
+            assert_eq!(sat, file.file_name().to_str().unwrap().contains(".sat"));
+
+            if sat {
+                let stack =
+                    ReconstructionStack::from_text(&solver.reconstruction_stack().to_string())
+                        .unwrap();
+                let model = stack.postsolve(&simplified_solver.assignment());
+                assert_eq!(model.len(), cnf.variable_count());
+                for clause in cnf.clauses() {
+                    assert!(clause.literals().iter().any(|l| model.contains(l)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_add_new_variables() {
+        let mut solver = Solver::from_clauses(vec![vec![1, 2], vec![-1, 2]]);
+        solver.add_clause(&[Literal::from_dimacs(-2), Literal::from_dimacs(4)]);
+        assert!(solver.solve());
+        assert_eq!(solver.assignment().len(), 4);
+        assert_eq!(solver.value_of(Literal::from_dimacs(4)), Some(true));
+    }
+
+    #[test]
+    fn test_new_variables_after_addition() {
+        let clauses = (1..=3)
+            .flat_map(|a| (4..=6).map(move |b| vec![a, b]))
+            .collect::<Vec<_>>();
+        let cnf = CNF::from_clauses(&clauses);
+        let mut solver = Solver::from_clauses(clauses);
+        for variable in 1..=6 {
+            solver.freeze(Variable::from_dimacs(variable));
+        }
+        solver.simplify();
+        assert_eq!(solver.search.bcp.assignment.variable_count(), 7);
+
+        // variable 7 of the user gets the variable after the auxiliary variable in the search
+        solver.add_clause(&[Literal::from_dimacs(-1), Literal::from_dimacs(7)]);
+        solver.add_clause(&[Literal::from_dimacs(-7)]);
+        solver.set_phase(Literal::from_dimacs(2));
+        assert_eq!(solver.search.bcp.assignment.variable_count(), 8);
+        assert!(solver.solve());
+
+        assert_eq!(solver.assignment().len(), 7);
+        assert_eq!(solver.value_of(Literal::from_dimacs(7)), Some(false));
+        assert_eq!(solver.value_of(Literal::from_dimacs(1)), Some(false));
+        assert_model(&cnf, &solver);
+
+        // the auxiliary variable follows the variables of the user in the simplified formula
+        let simplified = solver.simplified_cnf();
+        assert_eq!(simplified.variable_count(), 8);
+    }
+
+    #[test]
+    fn test_restore_eliminated_variables() {
+        let mut solver = Solver::from_clauses(vec![vec![-1, 2], vec![-2, 3]]);
+        solver.simplify();
+        assert!(!solver.reconstruction_stack().is_empty());
+
+        // 1 implies 3 in the original formula
+        solver.add_clause(&[Literal::from_dimacs(1)]);
+        solver.add_clause(&[Literal::from_dimacs(-3)]);
+        assert!(!solver.solve());
+    }
+
+    #[test]
+    fn test_value_of_eliminated_variables() {
+        let mut solver = Solver::from_clauses(vec![vec![-1, 2], vec![-2, 3]]);
+        solver.freeze(Variable::from_dimacs(1));
+        solver.freeze(Variable::from_dimacs(3));
+        solver.simplify();
+        assert!(solver
+            .reconstruction_stack()
+            .is_eliminated(Variable::from_dimacs(2)));
+
+        // 2 is still decided by the search, reconstruction flips it to satisfy the clauses it was
+        // eliminated with
+        solver.add_clause(&[Literal::from_dimacs(1)]);
+        assert!(solver.solve());
+        let assignment = solver.assignment();
+        assert!(assignment.contains(&Literal::from_dimacs(2)));
+        for literal in assignment {
+            assert_eq!(solver.value_of(literal), Some(true));
+            assert_eq!(solver.value_of(!literal), Some(false));
+        }
+
+        // a new clause discards the model, 2 is only assigned in the next one
+        solver.add_clause(&[Literal::from_dimacs(3)]);
+        assert_eq!(solver.value_of(Literal::from_dimacs(3)), Some(true));
+        assert_eq!(solver.value_of(Literal::from_dimacs(2)), None);
+        assert!(solver.solve());
+        assert_eq!(solver.value_of(Literal::from_dimacs(2)), Some(true));
+    }
+
+    #[test]
+    fn test_freeze_and_melt() {
+        let variable = Literal::from_dimacs(2).variable();
+
+        let mut solver = Solver::from_clauses(vec![vec![-1, 2], vec![-2, 3]]);
+        solver.freeze(Literal::from_dimacs(1).variable());
+        solver.freeze(Literal::from_dimacs(3).variable());
+        solver.freeze(variable);
+        solver.freeze(variable);
+        solver.melt(variable);
+        solver.simplify();
+        assert!(!solver.reconstruction_stack().is_eliminated(variable));
+
+        solver.melt(variable);
+        solver.simplify();
+        assert!(solver.reconstruction_stack().is_eliminated(variable));
+
+        solver.freeze(variable);
+        assert!(!solver.reconstruction_stack().is_eliminated(variable));
+        solver.add_clause(&[Literal::from_dimacs(2)]);
+        assert!(solver.solve());
+        assert_eq!(solver.value_of(Literal::from_dimacs(3)), Some(true));
+    }
+
+    #[test]
+    fn test_freeze_new_variable() {
+        let mut solver = Solver::from_clauses(vec![vec![1, 2]]);
+        let variable = Variable::from_dimacs(3);
+        solver.freeze(variable);
+        solver.add_clause(&[Literal::from_dimacs(-3), Literal::from_dimacs(2)]);
+        solver.add_clause(&[Literal::from_dimacs(-2), Literal::from_dimacs(1)]);
+        solver.simplify();
+        assert!(!solver.reconstruction_stack().is_eliminated(variable));
+        solver.melt(variable);
+
+        solver.add_clause(&[Literal::from_dimacs(3)]);
+        assert!(solver.solve());
+        assert_eq!(solver.value_of(Literal::from_dimacs(1)), Some(true));
+    }
+
+    #[test]
+    #[should_panic(expected = "not frozen")]
+    fn test_melt_unknown_variable() {
+        Solver::from_clauses(vec![vec![1, 2]]).melt(Variable::from_dimacs(3));
+    }
+
+    #[test]
+    fn test_inprocessing_during_search() {
+        let mut solver = Solver::from_cnf(CNF::from_file(PathBuf::from(
+            "../test_formulas/add128.unsat",
+        )));
+        assert!(!solver.solve());
+        let stats = solver.statistics();
+        assert!(stats.conflicts >= 2_000);
+        assert!(stats.inprocessing_rounds > 0);
+    }
+
+    fn assert_model(cnf: &CNF, solver: &Solver) {
+        let model = solver.assignment();
+        for clause in cnf.clauses() {
+            assert!(clause.literals().iter().any(|l| model.contains(l)));
+        }
+    }
 }
@@ -0,0 +1,77 @@
+use std::fmt;
+
+/// Counters collected by the solver
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct Statistics {
+    /// literals removed because they occurred more than once in an added clause
+    pub duplicate_literals: u64,
+    /// added clauses that contained a literal and its negation
+    pub tautologies: u64,
+    /// added clauses that were already satisfied on the top level
+    pub satisfied_clauses: u64,
+    /// literals removed from added clauses because they were false on the top level
+    pub false_literals: u64,
+    /// added clauses that were identical to a previously added clause
+    pub duplicate_clauses: u64,
+    /// conflicts found during the search
+    pub conflicts: u64,
+    /// conflicts found in focused mode
+    pub focused_conflicts: u64,
+    /// conflicts found in stable mode
+    pub stable_conflicts: u64,
+    /// switches between the focused and stable mode
+    pub mode_switches: u64,
+    /// restarts scheduled by the restart policy of the current mode
+    pub restarts: u64,
+    /// restarts that kept some decision levels of the trail
+    pub reused_trails: u64,
+    /// decision levels kept by restarts
+    pub reused_levels: u64,
+    /// reasons strengthened by on-the-fly subsumption during conflict analysis
+    pub strengthened_reasons: u64,
+    /// inprocessing rounds run during the search
+    pub inprocessing_rounds: u64,
+    /// failed literals found by probing during inprocessing
+    pub failed_literals: u64,
+    /// clauses shortened or removed by vivification during inprocessing
+    pub vivified_clauses: u64,
+    /// resolvents added by ternary resolution during inprocessing
+    pub ternary_resolvents: u64,
+    /// clauses removed or strengthened by subsumption during inprocessing
+    pub subsumed_clauses: u64,
+    /// variables eliminated during inprocessing
+    pub eliminated_variables: u64,
+    /// resets of the saved phases
+    pub rephases: u64,
+    /// reductions of the learned clauses
+    pub reductions: u64,
+    /// learned clauses deleted by reductions
+    pub reduced_clauses: u64,
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "c duplicate literals: {}", self.duplicate_literals)?;
+        writeln!(f, "c tautologies: {}", self.tautologies)?;
+        writeln!(f, "c satisfied clauses: {}", self.satisfied_clauses)?;
+        writeln!(f, "c false literals: {}", self.false_literals)?;
+        writeln!(f, "c duplicate clauses: {}", self.duplicate_clauses)?;
+        writeln!(f, "c conflicts: {}", self.conflicts)?;
+        writeln!(f, "c focused conflicts: {}", self.focused_conflicts)?;
+        writeln!(f, "c stable conflicts: {}", self.stable_conflicts)?;
+        writeln!(f, "c mode switches: {}", self.mode_switches)?;
+        writeln!(f, "c restarts: {}", self.restarts)?;
+        writeln!(f, "c reused trails: {}", self.reused_trails)?;
+        writeln!(f, "c reused levels: {}", self.reused_levels)?;
+        writeln!(f, "c strengthened reasons: {}", self.strengthened_reasons)?;
+        writeln!(f, "c inprocessing rounds: {}", self.inprocessing_rounds)?;
+        writeln!(f, "c failed literals: {}", self.failed_literals)?;
+        writeln!(f, "c vivified clauses: {}", self.vivified_clauses)?;
+        writeln!(f, "c ternary resolvents: {}", self.ternary_resolvents)?;
+        writeln!(f, "c subsumed clauses: {}", self.subsumed_clauses)?;
+        writeln!(f, "c eliminated variables: {}", self.eliminated_variables)?;
+        writeln!(f, "c rephases: {}", self.rephases)?;
+        writeln!(f, "c reductions: {}", self.reductions)?;
+        write!(f, "c reduced clauses: {}", self.reduced_clauses)
+    }
+}